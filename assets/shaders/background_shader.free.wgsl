@@ -0,0 +1,61 @@
+// Free placement, no cells: only small dots on whole world units to keep a sense of scale.
+
+struct Config {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    zoom: f32,
+
+    cx: f32,
+    cy: f32,
+    cz: f32,
+
+    sectors: f32,
+    scale_x: f32,
+    scale_y: f32,
+    rotation: f32,
+    octagon_cut: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> config: Config;
+
+// World position to the plain grid, the inverse of `GridParams::transform` in src/geometry.rs.
+fn to_grid(p: vec2<f32>) -> vec2<f32> {
+    let c = cos(config.rotation);
+    let s = sin(config.rotation);
+    return vec2<f32>(c * p.x + s * p.y, -s * p.x + c * p.y) / vec2<f32>(config.scale_x, config.scale_y);
+}
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+};
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(vertex.position, 1.0);
+
+    let uv = vec2<f32>(vertex.position.x * config.width * 0.5 - config.x, vertex.position.y * config.height * 0.5 - config.y);
+    out.position = to_grid(uv / config.zoom);
+    return out;
+}
+
+struct FragmentInput {
+    @location(0) position: vec2<f32>,
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let d = length(in.position - round(in.position));
+
+    var b = step(d, 0.03);
+
+    var bt = 1.0 - b;
+    return b * vec4(config.cx, config.cy, config.cz, 1.0) + bt * vec4(0.0, 0.0, 0.0, 1.0);
+}
@@ -9,11 +9,24 @@ struct Config {
     cx: f32,
     cy: f32,
     cz: f32,
+
+    sectors: f32,
+    scale_x: f32,
+    scale_y: f32,
+    rotation: f32,
+    octagon_cut: f32,
 };
 
 @group(0) @binding(0)
 var<uniform> config: Config;
 
+// World position to the plain grid, the inverse of `GridParams::transform` in src/geometry.rs.
+fn to_grid(p: vec2<f32>) -> vec2<f32> {
+    let c = cos(config.rotation);
+    let s = sin(config.rotation);
+    return vec2<f32>(c * p.x + s * p.y, -s * p.x + c * p.y) / vec2<f32>(config.scale_x, config.scale_y);
+}
+
 // NOTE: Bindings must come before functions that use them!
 // The structure of the vertex buffer is as specified in `specialize()`
 struct Vertex {
@@ -32,7 +45,7 @@ fn vertex(vertex: Vertex) -> VertexOutput {
     out.clip_position = vec4<f32>(vertex.position, 1.0);
 
     let uv = vec2<f32>(vertex.position.x * config.width * 0.5 - config.x, vertex.position.y * config.height * 0.5 - config.y);
-    out.position = vec2<f32>(uv / config.zoom);
+    out.position = to_grid(uv / config.zoom);
 
     out.position.x = out.position.x - 1.0;
     return out;
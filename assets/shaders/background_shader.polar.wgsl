@@ -0,0 +1,81 @@
+// Polar grid: ring 0 is a disk of radius 0.5, ring r is 1 wide and has sectors * r cells.
+// Keep in sync with src/geometry/polar.rs.
+
+struct Config {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    zoom: f32,
+
+    cx: f32,
+    cy: f32,
+    cz: f32,
+
+    sectors: f32,
+    scale_x: f32,
+    scale_y: f32,
+    rotation: f32,
+    octagon_cut: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> config: Config;
+
+// World position to the plain grid, the inverse of `GridParams::transform` in src/geometry.rs.
+fn to_grid(p: vec2<f32>) -> vec2<f32> {
+    let c = cos(config.rotation);
+    let s = sin(config.rotation);
+    return vec2<f32>(c * p.x + s * p.y, -s * p.x + c * p.y) / vec2<f32>(config.scale_x, config.scale_y);
+}
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+};
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(vertex.position, 1.0);
+
+    let uv = vec2<f32>(vertex.position.x * config.width * 0.5 - config.x, vertex.position.y * config.height * 0.5 - config.y);
+    out.position = to_grid(uv / config.zoom);
+    return out;
+}
+
+struct FragmentInput {
+    @location(0) position: vec2<f32>,
+};
+
+let TAU: f32 = 6.2831853;
+let RINGS: f32 = 64.0;
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let radius = length(in.position);
+    let ring = floor(radius + 0.5);
+
+    // circles between the rings
+    var d = abs(fract(radius + 0.5) - 0.5);
+
+    // spokes between the sectors, not inside the middle disk
+    if (ring >= 1.0 && ring <= RINGS) {
+        let span = TAU / (max(config.sectors, 4.0) * ring);
+        let angle = atan2(in.position.y, in.position.x);
+        let a = abs(fract(angle / span + 0.5) - 0.5) * span;
+        d = min(d, radius * sin(a));
+    }
+
+    if (radius > RINGS + 0.5 + 0.01) {
+        d = 1000.0;
+    }
+
+    var b = step(d, 0.01);
+
+    var bt = 1.0 - b;
+    return b * vec4(config.cx, config.cy, config.cz, 1.0) + bt * vec4(0.0, 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,105 @@
+// Snub square tiling: squares and triangles with edges of length 1.
+// The edges of one unit cell, the cell repeats along A and B.
+// Keep in sync with the tables in src/geometry/periodic.rs.
+
+struct Config {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    zoom: f32,
+
+    cx: f32,
+    cy: f32,
+    cz: f32,
+
+    sectors: f32,
+    scale_x: f32,
+    scale_y: f32,
+    rotation: f32,
+    octagon_cut: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> config: Config;
+
+// World position to the plain grid, the inverse of `GridParams::transform` in src/geometry.rs.
+fn to_grid(p: vec2<f32>) -> vec2<f32> {
+    let c = cos(config.rotation);
+    let s = sin(config.rotation);
+    return vec2<f32>(c * p.x + s * p.y, -s * p.x + c * p.y) / vec2<f32>(config.scale_x, config.scale_y);
+}
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+};
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(vertex.position, 1.0);
+
+    let uv = vec2<f32>(vertex.position.x * config.width * 0.5 - config.x, vertex.position.y * config.height * 0.5 - config.y);
+    out.position = to_grid(uv / config.zoom);
+    return out;
+}
+
+struct FragmentInput {
+    @location(0) position: vec2<f32>,
+};
+
+let A: vec2<f32> = vec2<f32>(1.9318517, 0.0000000);
+let B: vec2<f32> = vec2<f32>(0.0000000, 1.9318517);
+
+var<private> EDGES: array<vec4<f32>, 15> = array<vec4<f32>, 15>(
+    vec4<f32>(-0.3535534, -0.6123725, 0.6123724, -0.3535534),
+    vec4<f32>(0.3535534, 0.6123724, 0.6123724, -0.3535534),
+    vec4<f32>(0.3535534, 0.6123724, -0.6123725, 0.3535534),
+    vec4<f32>(-0.6123725, 0.3535534, -0.3535534, -0.6123725),
+    vec4<f32>(0.3535534, 0.6123724, 1.3194792, 0.3535534),
+    vec4<f32>(1.3194792, 0.3535534, 1.5782983, 1.3194792),
+    vec4<f32>(1.5782983, 1.3194792, 0.6123724, 1.5782983),
+    vec4<f32>(0.3535534, 0.6123724, 0.6123724, 1.5782983),
+    vec4<f32>(1.3194792, 0.3535534, 0.6123724, -0.3535534),
+    vec4<f32>(-0.3535534, 1.3194792, 0.6123724, 1.5782983),
+    vec4<f32>(0.3535534, 0.6123724, -0.3535534, 1.3194792),
+    vec4<f32>(2.2854051, 0.6123724, 1.3194792, 0.3535534),
+    vec4<f32>(2.2854051, 0.6123724, 1.5782983, 1.3194792),
+    vec4<f32>(1.3194792, 2.2854051, 1.5782983, 1.3194792),
+    vec4<f32>(1.3194792, 2.2854051, 0.6123724, 1.5782983)
+);
+
+fn segment_distance(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let det = A.x * B.y - A.y * B.x;
+    let u = floor((in.position.x * B.y - in.position.y * B.x) / det);
+    let v = floor((A.x * in.position.y - A.y * in.position.x) / det);
+
+    // edges reach at most one unit cell out of their own
+    var d = 1000.0;
+    for (var i = -1; i <= 1; i = i + 1) {
+        for (var j = -1; j <= 1; j = j + 1) {
+            let local = in.position - A * (u + f32(i)) - B * (v + f32(j));
+            for (var k = 0; k < 15; k = k + 1) {
+                let e = EDGES[k];
+                d = min(d, segment_distance(local, e.xy, e.zw));
+            }
+        }
+    }
+
+    var b = step(d, 0.01);
+
+    var bt = 1.0 - b;
+    return b * vec4(config.cx, config.cy, config.cz, 1.0) + bt * vec4(0.0, 0.0, 0.0, 1.0);
+}
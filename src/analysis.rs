@@ -0,0 +1,151 @@
+//! How balanced a map is, measured in the turns ships need once the map is exported.
+
+use crate::geometry::Point;
+use crate::planet::{PlanetData, Player};
+
+/// Factor that `get_planets_export` multiplies world positions with, so the two planets that
+/// are furthest apart end up `girth` apart. `None` when there are no two planets apart to scale.
+pub fn export_scale(girth: f32, points: &[Point]) -> Option<f32> {
+    let mut longest_dist = 0.0;
+    for p1 in points {
+        for p2 in points {
+            let d = (p1.x - p2.x).powi(2) + (p1.y - p2.y).powi(2);
+            if d > longest_dist {
+                longest_dist = d;
+            }
+        }
+    }
+    (longest_dist > 0.).then(|| girth / f32::sqrt(longest_dist))
+}
+
+/// Turns a fleet needs to fly `dist`, like planetwars rounds it.
+pub fn turns(dist: f32) -> u32 {
+    dist.ceil() as u32
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerReport {
+    pub player: Player,
+    /// Planets the player starts with.
+    pub planets: usize,
+    /// Ships on those planets.
+    pub ships: usize,
+    /// Turns from the closest planet of the player to every neutral planet, in the order the
+    /// planets were passed in.
+    pub neutral_turns: Vec<(String, u32)>,
+    /// Ships on the neutral planets that can be reached within the horizon.
+    pub reachable_ships: usize,
+    /// Turns to the closest planet of another player, `None` without opponents.
+    pub nearest_enemy: Option<u32>,
+    /// Ships the player starts with plus the neutral ships they reach first, ties are split.
+    pub claimed_ships: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub horizon: u32,
+    /// One report for every player that owns a planet, in player order.
+    pub players: Vec<PlayerReport>,
+    /// Claimed ships of the poorest player over those of the richest one, `1` is perfectly
+    /// fair. `None` with fewer than two players.
+    pub fairness: Option<f32>,
+}
+
+/// Analyzes `planets` at their world positions, scaled like an export with `girth`, looking
+/// `horizon` turns ahead for reachable neutral ships. `None` when the planets have no distance
+/// between them to measure turns with.
+pub fn analyze(planets: &[(Point, &PlanetData)], girth: f32, horizon: u32) -> Option<Analysis> {
+    let points: Vec<_> = planets.iter().map(|(p, _)| *p).collect();
+    let scale = export_scale(girth, &points)?;
+    let between = |a: usize, b: usize| turns(points[a].dist(points[b]) * scale);
+
+    let mut owners: Vec<_> = planets
+        .iter()
+        .map(|(_, d)| d.player)
+        .filter(|p| p.0 != 0)
+        .collect();
+    owners.sort();
+    owners.dedup();
+
+    let owned_by = |player: Player| {
+        (0..planets.len())
+            .filter(|i| planets[*i].1.player == player)
+            .collect::<Vec<_>>()
+    };
+    let neutrals = owned_by(Player(0));
+    let homes: Vec<_> = owners.iter().map(|p| owned_by(*p)).collect();
+
+    // turns from every player to every neutral planet
+    let from_home = |home: &[usize], target: usize| {
+        home.iter()
+            .map(|h| between(*h, target))
+            .min()
+            .unwrap_or(u32::MAX)
+    };
+    let distances: Vec<Vec<u32>> = homes
+        .iter()
+        .map(|home| neutrals.iter().map(|n| from_home(home, *n)).collect())
+        .collect();
+
+    let mut claimed: Vec<f32> = homes
+        .iter()
+        .map(|home| home.iter().map(|h| planets[*h].1.ship_count as f32).sum())
+        .collect();
+    for (j, n) in neutrals.iter().enumerate() {
+        let first = distances.iter().map(|d| d[j]).min().unwrap_or(u32::MAX);
+        let winners: Vec<_> = (0..owners.len())
+            .filter(|i| distances[*i][j] == first)
+            .collect();
+        for i in &winners {
+            claimed[*i] += planets[*n].1.ship_count as f32 / winners.len() as f32;
+        }
+    }
+
+    let players: Vec<_> = owners
+        .iter()
+        .enumerate()
+        .map(|(i, player)| {
+            let home = &homes[i];
+            let nearest_enemy = homes
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, other)| other.iter().map(|o| from_home(home, *o)))
+                .min();
+            PlayerReport {
+                player: *player,
+                planets: home.len(),
+                ships: home.iter().map(|h| planets[*h].1.ship_count).sum(),
+                neutral_turns: neutrals
+                    .iter()
+                    .zip(&distances[i])
+                    .map(|(n, d)| (planets[*n].1.name.clone(), *d))
+                    .collect(),
+                reachable_ships: neutrals
+                    .iter()
+                    .zip(&distances[i])
+                    .filter(|(_, d)| **d <= horizon)
+                    .map(|(n, _)| planets[*n].1.ship_count)
+                    .sum(),
+                nearest_enemy,
+                claimed_ships: claimed[i],
+            }
+        })
+        .collect();
+
+    let fairness = (players.len() >= 2).then(|| {
+        let most = claimed.iter().copied().fold(0., f32::max);
+        let least = claimed.iter().copied().fold(f32::MAX, f32::min);
+        if most > 0. {
+            least / most
+        } else {
+            1.
+        }
+    });
+
+    Some(Analysis {
+        horizon,
+        players,
+        fairness,
+    })
+}
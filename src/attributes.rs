@@ -0,0 +1,140 @@
+//! Extra planet attributes for game variants, like growth rate or production.
+//!
+//! Every attribute is declared once in [`ATTRIBUTES`], the editor, save files and the export
+//! all go through that list. Planets only store the attributes that differ from the default.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttributeKind {
+    Int {
+        min: i64,
+        max: i64,
+    },
+    Float {
+        min: f64,
+        max: f64,
+    },
+    Text,
+    /// Text edited as comma separated tags, exported as a list.
+    Tags,
+}
+
+#[derive(Debug)]
+pub struct Attribute {
+    /// Name in save files and the export.
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: AttributeKind,
+    pub default: AttributeValue,
+}
+
+/// Every attribute a planet can have, in the order the editor shows them.
+pub const ATTRIBUTES: &[Attribute] = &[
+    Attribute {
+        key: "growth_rate",
+        label: "growth rate",
+        kind: AttributeKind::Int { min: 0, max: 100 },
+        default: AttributeValue::Int(1),
+    },
+    Attribute {
+        key: "size",
+        label: "size",
+        kind: AttributeKind::Float { min: 0.1, max: 10. },
+        default: AttributeValue::Float(1.),
+    },
+    Attribute {
+        key: "production",
+        label: "production",
+        kind: AttributeKind::Int { min: 0, max: 1000 },
+        default: AttributeValue::Int(0),
+    },
+    Attribute {
+        key: "tags",
+        label: "tags",
+        kind: AttributeKind::Tags,
+        default: AttributeValue::Text(String::new()),
+    },
+];
+
+/// The attribute called `key`.
+pub fn attribute(key: &str) -> Option<&'static Attribute> {
+    ATTRIBUTES.iter().find(|a| a.key == key)
+}
+
+impl Attribute {
+    /// Whether `value` has the type of this attribute and lies in its range.
+    pub fn accepts(&self, value: &AttributeValue) -> bool {
+        match (self.kind, value) {
+            (AttributeKind::Int { min, max }, AttributeValue::Int(v)) => (min..=max).contains(v),
+            (AttributeKind::Float { min, max }, AttributeValue::Float(v)) => {
+                (min..=max).contains(v)
+            }
+            (AttributeKind::Text | AttributeKind::Tags, AttributeValue::Text(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// `value` as it goes into the export.
+    pub fn export(&self, value: &AttributeValue) -> Value {
+        match (self.kind, value) {
+            (AttributeKind::Tags, AttributeValue::Text(text)) => text
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .into(),
+            (_, AttributeValue::Int(v)) => (*v).into(),
+            (_, AttributeValue::Float(v)) => (*v).into(),
+            (_, AttributeValue::Text(v)) => v.as_str().into(),
+        }
+    }
+}
+
+/// Attributes of a planet that differ from their default. Keys that are not in
+/// [`ATTRIBUTES`] are kept as they are, so saves of newer versions lose nothing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Attributes(BTreeMap<String, AttributeValue>);
+
+impl Attributes {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value of `attr`, its default when it is unset or does not fit.
+    pub fn get(&self, attr: &Attribute) -> AttributeValue {
+        match self.0.get(attr.key) {
+            Some(value) if attr.accepts(value) => value.clone(),
+            _ => attr.default.clone(),
+        }
+    }
+
+    /// Sets `attr`, defaults are not stored.
+    pub fn set(&mut self, attr: &Attribute, value: AttributeValue) {
+        if value == attr.default {
+            self.0.remove(attr.key);
+        } else {
+            self.0.insert(attr.key.to_string(), value);
+        }
+    }
+
+    /// Every attribute of [`ATTRIBUTES`] with its export value, defaults included.
+    pub fn export(&self) -> serde_json::Map<String, Value> {
+        ATTRIBUTES
+            .iter()
+            .map(|attr| (attr.key.to_string(), attr.export(&self.get(attr))))
+            .collect()
+    }
+}
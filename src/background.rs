@@ -32,7 +32,10 @@ use bevy::{
     utils::FloatOrd,
 };
 
-use crate::map_config::{MapConfig, MapType};
+use crate::{
+    geometry::{GridParams, OctagonGrid},
+    map_config::{MapConfig, MapType},
+};
 
 fn setup_background(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
     let mut background = Mesh::new(PrimitiveTopology::TriangleList);
@@ -51,7 +54,7 @@ fn setup_background(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
     // We can now spawn the entities for the star and the camera
     commands.spawn_bundle((
         // We use a marker component to identify the custom colored meshes
-        BackgroundMesh2d::default(),
+        BackgroundMesh2d,
         // The `Handle<Mesh>` needs to be wrapped in a `Mesh2dHandle` to use 2d rendering instead of 3d
         Mesh2dHandle(meshes.add(background)),
         // These other components are needed for 2d meshes to be rendered
@@ -182,6 +185,7 @@ pub struct BackgroundConfig {
     pub height: f32,
     pub zoom: f32,
     pub color: Color,
+    pub params: GridParams,
 
     pub map_type: MapType,
 }
@@ -201,6 +205,7 @@ impl Default for BackgroundConfig {
             height: 0.,
             zoom: 0.,
             color: Color::BLACK,
+            params: GridParams::default(),
             map_type: MapType::Squares,
         }
     }
@@ -213,9 +218,10 @@ trait GetPod {
 }
 
 impl GetPod for BackgroundConfig {
-    type Inner = [f32; 8];
+    type Inner = [f32; 13];
 
     fn get(&self) -> Self::Inner {
+        let (scale, rotation) = self.params.transform(self.map_type);
         [
             self.x,
             self.y,
@@ -225,6 +231,13 @@ impl GetPod for BackgroundConfig {
             self.color.r(),
             self.color.g(),
             self.color.b(),
+            self.params.sectors as f32,
+            scale.x,
+            scale.y,
+            rotation,
+            self.params
+                .octagon_cut
+                .clamp(*OctagonGrid::CUTS.start(), *OctagonGrid::CUTS.end()),
         ]
     }
 }
@@ -241,6 +254,7 @@ impl ExtractResource for BackgroundConfig {
             width,
             height,
             bg_color,
+            params,
             ..
         } = *inp;
 
@@ -252,6 +266,7 @@ impl ExtractResource for BackgroundConfig {
             height,
 
             color: bg_color,
+            params,
             map_type: ty,
         }
     }
@@ -312,13 +327,24 @@ impl<const I: usize, T: Send + Sync + 'static> EntityRenderCommand
 
 pub struct BGShader {
     current: Handle<Shader>,
-    squares: (Option<Shader>, Handle<Shader>),
-    triangles: (Option<Shader>, Handle<Shader>),
-    hexagons: (Option<Shader>, Handle<Shader>),
-    octagons: (Option<Shader>, Handle<Shader>),
+    shaders: Vec<(MapType, Option<Shader>, Handle<Shader>)>,
     current_type: Option<MapType>,
 }
 
+fn shader_path(ty: MapType) -> &'static str {
+    match ty {
+        MapType::Squares => "shaders/background_shader.sq.wgsl",
+        MapType::Triangles => "shaders/background_shader.tri.wgsl",
+        MapType::Hexagons => "shaders/background_shader.hex.wgsl",
+        MapType::Octagons => "shaders/background_shader.oct.wgsl",
+        MapType::Rhombille => "shaders/background_shader.rhombille.wgsl",
+        MapType::Cairo => "shaders/background_shader.cairo.wgsl",
+        MapType::SnubSquare => "shaders/background_shader.snub.wgsl",
+        MapType::Free => "shaders/background_shader.free.wgsl",
+        MapType::Polar => "shaders/background_shader.polar.wgsl",
+    }
+}
+
 fn set_bg_shaders(
     mut bg_shader: ResMut<BGShader>,
     mut events: EventReader<AssetEvent<Shader>>,
@@ -327,24 +353,10 @@ fn set_bg_shaders(
     for event in events.iter() {
         match event {
             AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
-                if handle.id == bg_shader.squares.1.id {
-                    let shader = assets.get(handle).unwrap().clone();
-                    bg_shader.squares.0 = Some(shader);
-                }
-
-                if handle.id == bg_shader.triangles.1.id {
-                    let shader = assets.get(handle).unwrap().clone();
-                    bg_shader.triangles.0 = Some(shader);
-                }
-
-                if handle.id == bg_shader.hexagons.1.id {
-                    let shader = assets.get(handle).unwrap().clone();
-                    bg_shader.hexagons.0 = Some(shader);
-                }
-
-                if handle.id == bg_shader.octagons.1.id {
-                    let shader = assets.get(handle).unwrap().clone();
-                    bg_shader.octagons.0 = Some(shader);
+                for (_, shader, h) in bg_shader.shaders.iter_mut() {
+                    if handle.id == h.id {
+                        *shader = Some(assets.get(handle).unwrap().clone());
+                    }
                 }
             }
             _ => {}
@@ -364,33 +376,14 @@ fn update_bg_current_shader(
     };
 
     if changed {
-        let success = match (
-            config.ty,
-            &bg_shader.squares,
-            &bg_shader.triangles,
-            &bg_shader.hexagons,
-            &bg_shader.octagons,
-        ) {
-            (MapType::Squares, (Some(s), _), _, _, _) => {
-                assets.set_untracked(&bg_shader.current, s.clone());
-                true
-            }
-            (MapType::Triangles, _, (Some(s), _), _, _) => {
-                assets.set_untracked(&bg_shader.current, s.clone());
-                true
-            }
-            (MapType::Hexagons, _, _, (Some(s), _), _) => {
-                assets.set_untracked(&bg_shader.current, s.clone());
-                true
-            }
-            (MapType::Octagons, _, _, _, (Some(s), _)) => {
-                assets.set_untracked(&bg_shader.current, s.clone());
-                true
-            }
-            _ => false,
-        };
-
-        if success {
+        let shader = bg_shader
+            .shaders
+            .iter()
+            .find(|(ty, _, _)| *ty == config.ty)
+            .and_then(|(_, shader, _)| shader.clone());
+
+        if let Some(shader) = shader {
+            assets.set_untracked(&bg_shader.current, shader);
             bg_shader.current_type = Some(config.ty);
         }
     }
@@ -408,7 +401,7 @@ impl Plugin for BackgroundPlugin {
         let buffer = render_device.create_buffer(&BufferDescriptor {
             label: Some("background config uniform buffer"),
             // size: 80u64,
-            size: (std::mem::size_of::<f32>() * 9) as u64,
+            size: (std::mem::size_of::<f32>() * 13) as u64,
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -416,18 +409,15 @@ impl Plugin for BackgroundPlugin {
         let bg_shader = {
             let asset_server = app.world.resource::<AssetServer>();
             asset_server.watch_for_changes().unwrap();
-            let squares: Handle<Shader> = asset_server.load("shaders/background_shader.sq.wgsl");
-            let triangles: Handle<Shader> = asset_server.load("shaders/background_shader.tri.wgsl");
-            let hexagons: Handle<Shader> = asset_server.load("shaders/background_shader.hex.wgsl");
-            let octagons: Handle<Shader> = asset_server.load("shaders/background_shader.oct.wgsl");
+            let shaders = MapType::ALL
+                .into_iter()
+                .map(|ty| (ty, None, asset_server.load(shader_path(ty))))
+                .collect();
 
             let current = Handle::weak(HandleId::random::<Shader>());
             BGShader {
                 current,
-                squares: (None, squares),
-                triangles: (None, triangles),
-                hexagons: (None, hexagons),
-                octagons: (None, octagons),
+                shaders,
                 current_type: None,
             }
         };
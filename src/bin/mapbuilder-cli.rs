@@ -0,0 +1,368 @@
+//! The editor's save, validation and export steps without a window, for scripts.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use mapbuilder::{
+    analysis::{analyze, PlayerReport},
+    export::{format, ExportFormat, ExportMap, ExportOptions, FORMATS},
+    migration::load_scene,
+    planet::Players,
+    scene::Scene,
+    validation::{validate, Severity},
+};
+
+const USAGE: &str = "\
+Usage: mapbuilder-cli <command> [options]
+
+Commands:
+  convert <save> --girth <turns> [--format <format>] [--name <name>] [--attributes]
+          [--out <file>]
+      Writes the export of a save, to stdout without --out.
+  validate <save>... [--girth <turns>]
+      Lists the problems of every save, fails when one can not be exported.
+  stats <save> [--girth <turns>] [--horizon <turns>]
+      Prints the planets and ships of every player, with --girth also the analysis.
+  batch <dir> --girth <turns> [--format <format>] [--attributes] [--out <dir>]
+      Converts every save in a directory, to <dir>/export without --out.
+
+Options:
+  --girth <turns>    Longest expedition in turns, like the editor's export field.
+  --format <format>  planetwars (the default), csv, json-meta or svg.
+  --labels           Names and ship counts in the svg drawing.
+  --distances        Turns between close planets in the svg drawing.
+  --name <name>      Map name in the export, the file name without --name.
+  --attributes       Exports the planet attributes as well.
+  --horizon <turns>  Turns to look ahead for reachable neutral ships, 10 by default.
+  --out <path>       Where to write the export.";
+
+/// Analysis horizon when `--horizon` is not given, like the editor's.
+const HORIZON: u32 = 10;
+
+#[derive(Default)]
+struct Options {
+    paths: Vec<PathBuf>,
+    girth: Option<f32>,
+    format: Option<&'static dyn ExportFormat>,
+    name: Option<String>,
+    attributes: bool,
+    labels: bool,
+    distances: bool,
+    horizon: Option<u32>,
+    out: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value =
+                |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+            match arg.as_str() {
+                "--girth" => {
+                    let v = value("--girth")?;
+                    let girth =
+                        v.parse().ok().filter(|g: &f32| *g > 0.).ok_or_else(|| {
+                            format!("--girth must be a positive number, not {}", v)
+                        })?;
+                    options.girth = Some(girth);
+                }
+                "--format" => {
+                    let v = value("--format")?;
+                    let ids: Vec<_> = FORMATS.iter().map(|f| f.id()).collect();
+                    let format = format(&v).ok_or_else(|| {
+                        format!("Unknown format {}, pick one of {}", v, ids.join(", "))
+                    })?;
+                    options.format = Some(format);
+                }
+                "--name" => options.name = Some(value("--name")?),
+                "--attributes" => options.attributes = true,
+                "--labels" => options.labels = true,
+                "--distances" => options.distances = true,
+                "--horizon" => {
+                    let v = value("--horizon")?;
+                    let horizon = v
+                        .parse()
+                        .map_err(|_| format!("--horizon must be a number of turns, not {}", v))?;
+                    options.horizon = Some(horizon);
+                }
+                "--out" => options.out = Some(value("--out")?.into()),
+                flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+                path => options.paths.push(path.into()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn girth(&self) -> Result<f32, String> {
+        self.girth.ok_or_else(|| "--girth is needed".to_string())
+    }
+
+    fn format(&self) -> &'static dyn ExportFormat {
+        self.format.unwrap_or(FORMATS[0])
+    }
+
+    /// The only path, for commands that take one.
+    fn path(&self) -> Result<&Path, String> {
+        match self.paths.as_slice() {
+            [path] => Ok(path),
+            [] => Err("No file given".to_string()),
+            _ => Err("Only one file can be given".to_string()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    let command = args.next();
+    let result = Options::parse(args).and_then(|options| match command.as_deref() {
+        Some("convert") => convert(&options),
+        Some("validate") => check(&options),
+        Some("stats") => stats(&options),
+        Some("batch") => batch(&options),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        Some(command) => Err(format!("Unknown command {}\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    });
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Scene, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    load_scene(&text)
+        .map(|(scene, _)| scene)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write(path: &Path, content: &[u8]) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The export of the save at `path`, or the validation errors that keep it from being exported.
+fn export_save(path: &Path, options: &Options) -> Result<Vec<u8>, String> {
+    let scene = read(path)?;
+    let girth = options.girth()?;
+    let grid = scene.config.grid();
+    let planets: Vec<_> = scene
+        .planets
+        .iter()
+        .map(|p| (p.location, &p.data))
+        .collect();
+
+    let players = scene.players.clone().unwrap_or_default();
+    let errors: Vec<_> = validate(&planets, grid.as_ref(), &players, Some(girth))
+        .into_iter()
+        .filter(|i| i.severity() == Severity::Error)
+        .map(|i| format!("{}: {}", path.display(), i.message))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    let name = match &options.name {
+        Some(name) => name.clone(),
+        None => file_name(path),
+    };
+    Ok(options.format().export(&ExportMap {
+        planets: &planets,
+        config: &scene.config,
+        players: &players,
+        options: &ExportOptions {
+            girth,
+            name,
+            attributes: options.attributes,
+            labels: options.labels,
+            distances: options.distances,
+        },
+    }))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn convert(options: &Options) -> Result<bool, String> {
+    let content = export_save(options.path()?, options)?;
+    match &options.out {
+        Some(out) => write(out, &content)?,
+        None => {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(&content)
+                .and_then(|()| writeln!(stdout))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(true)
+}
+
+/// Prints the problems of every save, `false` when one of them can not be exported.
+fn check(options: &Options) -> Result<bool, String> {
+    if options.paths.is_empty() {
+        return Err("No file given".to_string());
+    }
+    let mut ok = true;
+    for path in options.paths.iter() {
+        let scene = match read(path) {
+            Ok(scene) => scene,
+            Err(e) => {
+                println!("{}", e);
+                ok = false;
+                continue;
+            }
+        };
+        let planets: Vec<_> = scene
+            .planets
+            .iter()
+            .map(|p| (p.location, &p.data))
+            .collect();
+        let players = scene.players.clone().unwrap_or_default();
+        let issues = validate(
+            &planets,
+            scene.config.grid().as_ref(),
+            &players,
+            options.girth,
+        );
+        if issues.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        for issue in issues {
+            let severity = match issue.severity() {
+                Severity::Error => {
+                    ok = false;
+                    "error"
+                }
+                Severity::Warning => "warning",
+            };
+            println!("{}: {}: {}", path.display(), severity, issue.message);
+        }
+    }
+    Ok(ok)
+}
+
+fn stats(options: &Options) -> Result<bool, String> {
+    let scene = read(options.path()?)?;
+    let players = scene.players.clone().unwrap_or_default();
+    let grid = scene.config.grid();
+
+    println!("Map type: {:?}", scene.config.ty);
+    println!("Planets: {}", scene.planets.len());
+    for (player, info) in players.iter() {
+        let owned: Vec<_> = scene
+            .planets
+            .iter()
+            .filter(|p| p.data.player == player)
+            .collect();
+        if owned.is_empty() {
+            continue;
+        }
+        let ships: usize = owned.iter().map(|p| p.data.ship_count).sum();
+        println!(
+            "{}: {} planets with {} ships",
+            info.name,
+            owned.len(),
+            ships
+        );
+    }
+
+    let girth = match options.girth {
+        Some(girth) => girth,
+        None => return Ok(true),
+    };
+    let horizon = options.horizon.unwrap_or(HORIZON);
+    let planets: Vec<_> = scene
+        .planets
+        .iter()
+        .map(|p| (grid.center(p.location), &p.data))
+        .collect();
+    println!();
+    let analysis = match analyze(&planets, girth, horizon) {
+        Some(analysis) => analysis,
+        None => {
+            println!("Turns: planets need some distance between them");
+            return Ok(true);
+        }
+    };
+    match analysis.fairness {
+        Some(f) => println!("Score: {:.0}%", f * 100.),
+        None => println!("Score: needs at least two players"),
+    }
+    for report in analysis.players.iter() {
+        print_report(&players, report, horizon);
+    }
+    Ok(true)
+}
+
+fn print_report(players: &Players, report: &PlayerReport, horizon: u32) {
+    println!("{}:", players.name(report.player));
+    println!(
+        "  {} neutral ships within {} turns",
+        report.reachable_ships, horizon
+    );
+    println!("  {:.1} ships claimed first", report.claimed_ships);
+    if let Some(d) = report.nearest_enemy {
+        println!("  nearest enemy in {} turns", d);
+    }
+}
+
+/// Converts every `.json` file of the directory, `false` when one of them fails.
+fn batch(options: &Options) -> Result<bool, String> {
+    let dir = options.path()?;
+    options.girth()?;
+    let out = match &options.out {
+        Some(out) => out.clone(),
+        None => dir.join("export"),
+    };
+    fs::create_dir_all(&out).map_err(|e| format!("{}: {}", out.display(), e))?;
+
+    let mut saves: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    saves.sort();
+
+    let mut ok = true;
+    for save in saves {
+        // every save is named after its own file
+        let options = Options {
+            girth: options.girth,
+            format: options.format,
+            attributes: options.attributes,
+            labels: options.labels,
+            distances: options.distances,
+            ..Options::default()
+        };
+        let target = out.join(format!(
+            "{}.{}",
+            file_name(&save),
+            options.format().extension()
+        ));
+        match export_save(&save, &options).and_then(|content| write(&target, &content)) {
+            Ok(()) => println!("{} -> {}", save.display(), target.display()),
+            Err(e) => {
+                println!("{}", e);
+                ok = false;
+            }
+        }
+    }
+    Ok(ok)
+}
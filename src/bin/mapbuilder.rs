@@ -4,11 +4,16 @@ use bevy_egui::EguiPlugin;
 #[cfg(not(target_family = "wasm"))]
 use bevy_framepace::{FramepaceSettings, Limiter};
 use mapbuilder::{
-    self, input,
+    self,
+    clipboard::ClipboardPlugin,
+    history::HistoryPlugin,
+    input,
     map_config::{MapConfig, MapConfigPlugin},
     planet::{HoverPlanet, Location, PlanetPlugin},
     scene,
+    selection::SelectionPlugin,
     ui::UIPlugin,
+    validation::ValidationPlugin,
 };
 
 fn main() {
@@ -30,6 +35,10 @@ fn main() {
     .add_plugin(UIPlugin)
     .add_plugin(input::InputPlugin)
     .add_plugin(PlanetPlugin)
+    .add_plugin(ValidationPlugin)
+    .add_plugin(HistoryPlugin)
+    .add_plugin(SelectionPlugin)
+    .add_plugin(ClipboardPlugin)
     .add_plugin(mapbuilder::background::BackgroundPlugin)
     .add_startup_system(setup)
     .add_system(transform_hover_planet);
@@ -97,7 +97,7 @@ fn star(
     // We can now spawn the entities for the star and the camera
     commands.spawn_bundle((
         // We use a marker component to identify the custom colored meshes
-        ColoredMesh2d::default(),
+        ColoredMesh2d,
         // The `Handle<Mesh>` needs to be wrapped in a `Mesh2dHandle` to use 2d rendering instead of 3d
         Mesh2dHandle(meshes.add(star)),
         // These other components are needed for 2d meshes to be rendered
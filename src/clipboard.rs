@@ -0,0 +1,331 @@
+//! Copying, cutting and pasting groups of planets, also between maps.
+//!
+//! The clipboard holds a [`Scene`] with only the copied planets, so a save file can be pasted
+//! too. A paste follows the mouse until it is placed with a left click, or dropped with a
+//! right click or Esc.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiClipboard, EguiContext};
+
+use crate::{
+    eprintit,
+    geometry::{Grid, Point},
+    input,
+    map_config::MapConfig,
+    migration::{load_scene, VERSION},
+    planet::{HoverPlanet, Location, PlanetData, PlanetEvent, Selected},
+    scene::{Scene, SceneConfig, ScenePlanet},
+    selection::{plan_drop, MovePreview},
+    HoveringUI,
+};
+
+pub enum ClipboardEvent {
+    Copy,
+    Cut,
+    Paste,
+    Pasted(String),
+}
+
+/// Planets waiting to be placed, relative to the one that lands under the mouse.
+#[derive(Clone, Debug)]
+pub struct Paste {
+    pub planets: Vec<(Point, PlanetData)>,
+}
+
+impl Paste {
+    /// `None` when the scene has no planets. The planet closest to the middle of the group
+    /// follows the mouse.
+    pub fn new(scene: Scene) -> Option<Self> {
+        let grid = scene.config.grid();
+        let points: Vec<_> = scene
+            .planets
+            .iter()
+            .map(|p| grid.center(p.location))
+            .collect();
+        let n = points.len() as f32;
+        let mid = points.iter().fold(Point::default(), |m, p| {
+            Point::new(m.x + p.x / n, m.y + p.y / n)
+        });
+        let anchor = *points
+            .iter()
+            .min_by(|a, b| a.dist(mid).total_cmp(&b.dist(mid)))?;
+
+        let planets = points
+            .into_iter()
+            .zip(scene.planets)
+            .map(|(p, planet)| (Point::new(p.x - anchor.x, p.y - anchor.y), planet.data))
+            .collect();
+        Some(Self { planets })
+    }
+
+    /// Cells the planets land on when the middle one is dropped on `at`.
+    pub fn plan(&self, grid: &dyn Grid, at: Location, occupied: &[Location]) -> MovePreview {
+        let c = grid.center(at);
+        let points: Vec<_> = self
+            .planets
+            .iter()
+            .map(|(p, _)| Point::new(c.x + p.x, c.y + p.y))
+            .collect();
+        plan_drop(grid, &points, occupied)
+    }
+}
+
+/// The paste in progress, if any.
+#[derive(Default)]
+pub struct Clipboard {
+    pub pending: Option<Paste>,
+}
+
+pub struct ClipboardPlugin;
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Clipboard>()
+            .add_event::<ClipboardEvent>()
+            .add_plugin(io::IOPlugin)
+            .add_system(clipboard_keys)
+            .add_system(handle_clipboard_events)
+            // the click that places a paste must not create a planet as well
+            .add_system(place_paste.after(input::spawn_planet));
+    }
+}
+
+/// Copies the planets to a scene on the clipboard.
+pub fn copy<'a>(
+    config: SceneConfig,
+    planets: impl IntoIterator<Item = (Location, &'a PlanetData)>,
+) -> String {
+    let scene = Scene {
+        version: VERSION,
+        config,
+        planets: planets
+            .into_iter()
+            .map(|(location, data)| ScenePlanet {
+                data: data.clone(),
+                location,
+            })
+            .collect(),
+        players: None,
+    };
+    serde_json::to_string_pretty(&scene).unwrap()
+}
+
+/// Keeps `names` apart from `taken` and each other, by numbering the ones that collide.
+pub fn unique_names<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    taken: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut used: HashSet<String> = taken.into_iter().map(str::to_string).collect();
+    names
+        .into_iter()
+        .map(|name| {
+            let mut unique = name.to_string();
+            let mut i = 2;
+            while used.contains(&unique) {
+                unique = format!("{} {}", name, i);
+                i += 1;
+            }
+            used.insert(unique.clone());
+            unique
+        })
+        .collect()
+}
+
+fn clipboard_keys(
+    keys: Res<Input<KeyCode>>,
+    mut egui_context: ResMut<EguiContext>,
+    mut events: EventWriter<ClipboardEvent>,
+) {
+    // text fields copy and paste text
+    if egui_context.ctx_mut().wants_keyboard_input()
+        || !keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+    {
+        return;
+    }
+    if keys.just_pressed(KeyCode::C) {
+        events.send(ClipboardEvent::Copy);
+    }
+    if keys.just_pressed(KeyCode::X) {
+        events.send(ClipboardEvent::Cut);
+    }
+    if keys.just_pressed(KeyCode::V) {
+        events.send(ClipboardEvent::Paste);
+    }
+}
+
+fn handle_clipboard_events(
+    mut events: EventReader<ClipboardEvent>,
+    planets: Query<(Entity, &Location, &PlanetData, &Selected)>,
+    config: Res<MapConfig>,
+    mut system_clipboard: ResMut<EguiClipboard>,
+    mut clipboard: ResMut<Clipboard>,
+    mut planet_events: EventWriter<PlanetEvent>,
+) {
+    for event in events.iter() {
+        let text = match event {
+            ClipboardEvent::Copy | ClipboardEvent::Cut => {
+                let selected: Vec<_> = planets.iter().filter(|(_, _, _, s)| s.0).collect();
+                if selected.is_empty() {
+                    continue;
+                }
+                let text = copy(
+                    SceneConfig::new(&config),
+                    selected.iter().map(|(_, l, d, _)| (**l, *d)),
+                );
+                io::write(&mut system_clipboard, &text);
+                if let ClipboardEvent::Cut = event {
+                    planet_events.send_batch(
+                        selected
+                            .iter()
+                            .map(|(id, _, _, _)| PlanetEvent::Delete { id: *id }),
+                    );
+                }
+                continue;
+            }
+            // on the web the text comes back later as `Pasted`
+            ClipboardEvent::Paste => match io::read(&mut system_clipboard) {
+                Some(text) => text,
+                None => continue,
+            },
+            ClipboardEvent::Pasted(text) => text.clone(),
+        };
+
+        match load_scene(&text) {
+            Ok((scene, _)) => clipboard.pending = Paste::new(scene),
+            Err(e) => eprintit!("Nothing to paste: {}", e),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_paste(
+    mut clipboard: ResMut<Clipboard>,
+    mut preview: ResMut<MovePreview>,
+    hover: Query<&Location, With<HoverPlanet>>,
+    planets: Query<(&Location, &PlanetData), Without<HoverPlanet>>,
+    click: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    hovering_ui: Res<HoveringUI>,
+    config: Res<MapConfig>,
+    mut planet_events: EventWriter<PlanetEvent>,
+) {
+    let paste = match &clipboard.pending {
+        Some(paste) => paste,
+        None => return,
+    };
+    if keys.just_pressed(KeyCode::Escape) || click.just_pressed(MouseButton::Right) {
+        clipboard.pending = None;
+        *preview = MovePreview::default();
+        return;
+    }
+
+    let occupied: Vec<_> = planets.iter().map(|(l, _)| *l).collect();
+    let plan = paste.plan(config.grid().as_ref(), *hover.single(), &occupied);
+
+    if click.just_pressed(MouseButton::Left) && !hovering_ui.0 {
+        if !plan.blocked {
+            let names = unique_names(
+                paste.planets.iter().map(|(_, d)| d.name.as_str()),
+                planets.iter().map(|(_, d)| d.name.as_str()),
+            );
+            let events: Vec<_> = paste
+                .planets
+                .iter()
+                .zip(plan.targets)
+                .zip(names)
+                .map(|(((_, data), loc), name)| PlanetEvent::CreateNamed {
+                    loc,
+                    data: PlanetData {
+                        name,
+                        ..data.clone()
+                    },
+                })
+                .collect();
+            planet_events.send_batch(events.into_iter());
+            clipboard.pending = None;
+            *preview = MovePreview::default();
+        }
+        return;
+    }
+
+    if *preview != plan {
+        *preview = plan;
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod io {
+    use bevy::prelude::Plugin;
+    use bevy_egui::EguiClipboard;
+
+    pub struct IOPlugin;
+    impl Plugin for IOPlugin {
+        fn build(&self, _app: &mut bevy::prelude::App) {}
+    }
+
+    pub fn write(clipboard: &mut EguiClipboard, content: &str) {
+        clipboard.set_contents(content);
+    }
+
+    pub fn read(clipboard: &mut EguiClipboard) -> Option<String> {
+        clipboard.get_contents()
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod io {
+    use std::sync::Mutex;
+
+    use bevy::prelude::{EventWriter, Plugin};
+    use bevy_egui::EguiClipboard;
+    use wasm_bindgen::prelude::wasm_bindgen;
+
+    use super::ClipboardEvent;
+
+    mod js {
+        use wasm_bindgen::prelude::wasm_bindgen;
+
+        #[wasm_bindgen]
+        extern "C" {
+            #[wasm_bindgen(js_namespace = ["window", "scene"])]
+            pub fn copy(s: &str);
+
+            #[wasm_bindgen(js_namespace = ["window", "scene"])]
+            pub fn paste();
+        }
+    }
+
+    pub struct IOPlugin;
+    impl Plugin for IOPlugin {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_system(complete_paste);
+        }
+    }
+
+    fn complete_paste(mut ev: EventWriter<ClipboardEvent>) {
+        if let Ok(mut x) = PASTE.lock() {
+            if let Some(st) = x.take() {
+                ev.send(ClipboardEvent::Pasted(st));
+            }
+        }
+    }
+
+    static PASTE: Mutex<Option<String>> = Mutex::new(None);
+
+    pub fn write(_clipboard: &mut EguiClipboard, content: &str) {
+        js::copy(content);
+    }
+
+    pub fn read(_clipboard: &mut EguiClipboard) -> Option<String> {
+        js::paste();
+        None
+    }
+
+    #[wasm_bindgen]
+    pub fn finish_paste(st: &str) {
+        if let Ok(mut x) = PASTE.lock() {
+            *x = Some(st.to_string());
+        }
+    }
+}
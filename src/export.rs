@@ -0,0 +1,249 @@
+//! The formats a map can be exported to.
+//!
+//! Every format is listed once in [`FORMATS`], the editor's format picker and the command line
+//! both go through that list. Positions are scaled like the planetwars export in every format,
+//! so the two planets furthest apart are the longest expedition apart.
+
+pub mod svg;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    analysis::export_scale,
+    attributes::ATTRIBUTES,
+    geometry::Point,
+    migration::VERSION,
+    planet::{Location, PlanetData, Players},
+    scene::SceneConfig,
+};
+
+/// Settings of the export form.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// Longest expedition in turns.
+    pub girth: f32,
+    pub name: String,
+    /// Whether the planet attributes are exported as well.
+    pub attributes: bool,
+    /// Names and ship counts in the [`svg::Svg`] drawing.
+    pub labels: bool,
+    /// Lines with the turns between close planets in the [`svg::Svg`] drawing.
+    pub distances: bool,
+}
+
+/// Everything a format gets to write.
+pub struct ExportMap<'a> {
+    pub planets: &'a [(Location, &'a PlanetData)],
+    pub config: &'a SceneConfig,
+    pub players: &'a Players,
+    pub options: &'a ExportOptions,
+}
+
+pub trait ExportFormat: Sync {
+    /// Name on the command line.
+    fn id(&self) -> &'static str;
+    /// Name in the format picker.
+    fn label(&self) -> &'static str;
+    /// Extension of the exported file, without the dot.
+    fn extension(&self) -> &'static str;
+    fn export(&self, map: &ExportMap) -> Vec<u8>;
+}
+
+/// Every export format, the planetwars one first.
+pub const FORMATS: &[&dyn ExportFormat] = &[&PlanetWars, &Csv, &PrettyJson, &svg::Svg];
+
+/// The format called `id`.
+pub fn format(id: &str) -> Option<&'static dyn ExportFormat> {
+    FORMATS.iter().copied().find(|f| f.id() == id)
+}
+
+#[derive(Serialize)]
+struct Planet<'a> {
+    name: &'a str,
+    x: f32,
+    y: f32,
+    owner: Option<usize>,
+    ship_count: usize,
+    #[serde(flatten)]
+    attributes: serde_json::Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct ExportPlayer<'a> {
+    id: usize,
+    name: &'a str,
+    team: usize,
+}
+
+impl ExportMap<'_> {
+    /// World positions of the planets, scaled for the export.
+    pub fn points(&self) -> Vec<Point> {
+        let grid = self.config.grid();
+        let points: Vec<_> = self.planets.iter().map(|(l, _)| grid.center(*l)).collect();
+        // validation refuses maps without spread, their points stay where they are
+        let scale = export_scale(self.options.girth, &points).unwrap_or(1.);
+        points
+            .into_iter()
+            .map(|p| Point::new(p.x * scale, p.y * scale))
+            .collect()
+    }
+
+    fn export_planets(&self) -> Vec<Planet<'_>> {
+        self.planets
+            .iter()
+            .zip(self.points())
+            .map(|((_, data), p)| Planet {
+                name: &data.name,
+                x: p.x,
+                y: p.y,
+                owner: (!data.player.is_neutral()).then_some(data.player.0),
+                ship_count: data.ship_count,
+                attributes: if self.options.attributes {
+                    data.attributes.export()
+                } else {
+                    serde_json::Map::new()
+                },
+            })
+            .collect()
+    }
+
+    /// Owners by number, with the names and teams they have in the editor.
+    fn export_players(&self) -> Vec<ExportPlayer<'_>> {
+        self.players
+            .iter()
+            .skip(1)
+            .map(|(player, info)| ExportPlayer {
+                id: player.0,
+                name: &info.name,
+                team: info.team,
+            })
+            .collect()
+    }
+}
+
+/// The JSON that planetwars reads.
+pub struct PlanetWars;
+
+impl PlanetWars {
+    pub fn json(map: &ExportMap) -> Value {
+        serde_json::json!({
+            "planets": map.export_planets(),
+            "name": map.options.name,
+        })
+    }
+}
+
+impl ExportFormat for PlanetWars {
+    fn id(&self) -> &'static str {
+        "planetwars"
+    }
+
+    fn label(&self) -> &'static str {
+        "Planetwars JSON"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, map: &ExportMap) -> Vec<u8> {
+        Self::json(map).to_string().into_bytes()
+    }
+}
+
+/// One planet per line, for spreadsheets.
+pub struct Csv;
+
+impl Csv {
+    /// `field` quoted when it has to be.
+    fn field(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+impl ExportFormat for Csv {
+    fn id(&self) -> &'static str {
+        "csv"
+    }
+
+    fn label(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, map: &ExportMap) -> Vec<u8> {
+        let planets = map.export_planets();
+        let mut header = vec!["name", "x", "y", "owner", "ship_count"];
+        if map.options.attributes {
+            header.extend(ATTRIBUTES.iter().map(|a| a.key));
+        }
+
+        let mut out = header.join(",");
+        out.push('\n');
+        for planet in planets.iter() {
+            let mut fields = vec![
+                Self::field(planet.name),
+                planet.x.to_string(),
+                planet.y.to_string(),
+                planet.owner.map(|o| o.to_string()).unwrap_or_default(),
+                planet.ship_count.to_string(),
+            ];
+            for key in &header[5..] {
+                let value = match planet.attributes.get(*key) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Array(tags)) => tags
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                    Some(v) => v.to_string(),
+                    None => String::new(),
+                };
+                fields.push(Self::field(&value));
+            }
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out.into_bytes()
+    }
+}
+
+/// The planetwars JSON, indented and with what it was made from. The names and teams of the
+/// players come along as well, planetwars itself has no place for them.
+pub struct PrettyJson;
+
+impl ExportFormat for PrettyJson {
+    fn id(&self) -> &'static str {
+        "json-meta"
+    }
+
+    fn label(&self) -> &'static str {
+        "JSON with metadata"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, map: &ExportMap) -> Vec<u8> {
+        let mut json = PlanetWars::json(map);
+        json["players"] = serde_json::json!(map.export_players());
+        json["metadata"] = serde_json::json!({
+            "generator": concat!("mapbuilder ", env!("CARGO_PKG_VERSION")),
+            "save_version": VERSION,
+            "map_type": map.config.ty,
+            "symmetry": map.config.symmetry,
+            "longest_expedition": map.options.girth,
+            "planets": map.planets.len(),
+        });
+        serde_json::to_vec_pretty(&json).unwrap()
+    }
+}
@@ -0,0 +1,270 @@
+//! A drawing of the map like the editor shows it, for announcements and documentation.
+//!
+//! Everything is drawn from the [`Grid`] polygons, so it needs no renderer and works headless.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Write,
+};
+
+use super::{ExportFormat, ExportMap};
+use crate::{
+    analysis::{export_scale, turns},
+    geometry::{Grid, Location, Point},
+    map_config::MapType,
+};
+
+/// Pixels per world unit.
+const PIXELS: f32 = 40.;
+/// Cells drawn around the planets, in cell sizes.
+const MARGIN: f32 = 1.5;
+/// Most cells drawn to fill the view, maps spread out further only get the cells around their
+/// planets.
+const MAX_CELLS: usize = 10_000;
+/// Steps around every planet that are drawn when the view has too many cells.
+const NEAR_STEPS: u32 = 2;
+/// Planets a distance line is drawn to, the closest ones.
+const NEIGHBORS: usize = 3;
+
+const BACKGROUND: &str = "#1b1b1b";
+const GRID: &str = "#4a4a4a";
+
+/// An SVG drawing of the grid with the planets in their player's colors.
+pub struct Svg;
+
+impl ExportFormat for Svg {
+    fn id(&self) -> &'static str {
+        "svg"
+    }
+
+    fn label(&self) -> &'static str {
+        "SVG drawing"
+    }
+
+    fn extension(&self) -> &'static str {
+        "svg"
+    }
+
+    fn export(&self, map: &ExportMap) -> Vec<u8> {
+        draw(map).into_bytes()
+    }
+}
+
+fn draw(map: &ExportMap) -> String {
+    let grid = map.config.grid();
+    let (scale, _) = map.config.params.transform(map.config.ty);
+    let cell = scale.x.max(scale.y);
+    let centers: Vec<_> = map.planets.iter().map(|(l, _)| grid.center(*l)).collect();
+
+    // the view, in world units
+    let (min_x, max_x, min_y, max_y) = centers.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(a, b, c, d), p| (a.min(p.x), b.max(p.x), c.min(p.y), d.max(p.y)),
+    );
+    let (min_x, max_x, min_y, max_y) = if centers.is_empty() {
+        (-cell, cell, -cell, cell)
+    } else {
+        (
+            min_x - MARGIN * cell,
+            max_x + MARGIN * cell,
+            min_y - MARGIN * cell,
+            max_y + MARGIN * cell,
+        )
+    };
+    let (width, height) = (max_x - min_x, max_y - min_y);
+
+    let mut svg = String::new();
+    // svg y grows downwards, world y upwards
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="{} {} {} {}">"#,
+        width * PIXELS,
+        height * PIXELS,
+        min_x,
+        -max_y,
+        width,
+        height
+    )
+    .unwrap();
+    writeln!(svg, r#"<title>{}</title>"#, escape(&map.options.name)).unwrap();
+    writeln!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+        min_x, -max_y, width, height, BACKGROUND
+    )
+    .unwrap();
+
+    // free placement has no cells to show
+    if map.config.ty != MapType::Free {
+        writeln!(
+            svg,
+            r#"<g fill="none" stroke="{}" stroke-width="{}">"#,
+            GRID,
+            0.03 * cell
+        )
+        .unwrap();
+        let starts: Vec<_> = map.planets.iter().map(|(l, _)| *l).collect();
+        for loc in cells(grid.as_ref(), (min_x, max_x, min_y, max_y), &starts) {
+            writeln!(svg, r#"<polygon points="{}"/>"#, points(&grid.polygon(loc))).unwrap();
+        }
+        svg.push_str("</g>\n");
+    }
+
+    if map.options.distances {
+        distances(&mut svg, map, &centers, cell);
+    }
+
+    writeln!(svg, r#"<g stroke="white" stroke-width="{}">"#, 0.04 * cell).unwrap();
+    for (loc, data) in map.planets {
+        let color = map.players.color32(data.player);
+        writeln!(
+            svg,
+            r##"<polygon points="{}" fill="#{:02x}{:02x}{:02x}"/>"##,
+            points(&grid.polygon(*loc)),
+            color.r(),
+            color.g(),
+            color.b()
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+
+    if map.options.labels {
+        writeln!(
+            svg,
+            r#"<g font-family="sans-serif" font-size="{}" text-anchor="middle" fill="white" stroke="black" stroke-width="{}" paint-order="stroke">"#,
+            0.3 * cell,
+            0.05 * cell
+        )
+        .unwrap();
+        for ((_, data), c) in map.planets.iter().zip(&centers) {
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                c.x,
+                -c.y - 0.05 * cell,
+                escape(&data.name)
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                c.x,
+                -c.y + 0.3 * cell,
+                data.ship_count
+            )
+            .unwrap();
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Every cell of `grid` that has its center in the view, found by walking out from the planets
+/// on `starts`. Only the cells close to the planets when there are more than [`MAX_CELLS`].
+fn cells(
+    grid: &dyn Grid,
+    (min_x, max_x, min_y, max_y): (f32, f32, f32, f32),
+    starts: &[Location],
+) -> Vec<Location> {
+    let inside = |loc: Location, _| {
+        let c = grid.center(loc);
+        (min_x..=max_x).contains(&c.x) && (min_y..=max_y).contains(&c.y)
+    };
+    flood(grid, starts, inside, MAX_CELLS)
+        .or_else(|| flood(grid, starts, |_, steps| steps <= NEAR_STEPS, usize::MAX))
+        .unwrap_or_default()
+}
+
+/// Cells reachable from `starts` through cells that are `keep`, which gets the steps from the
+/// closest start. `None` once more than `limit` are found.
+fn flood(
+    grid: &dyn Grid,
+    starts: &[Location],
+    keep: impl Fn(Location, u32) -> bool,
+    limit: usize,
+) -> Option<Vec<Location>> {
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<_> = starts
+        .iter()
+        .filter(|l| grid.is_cell(**l) && seen.insert(**l))
+        .map(|l| (*l, 0))
+        .collect();
+    let mut found = Vec::new();
+    while let Some((loc, steps)) = queue.pop_front() {
+        found.push(loc);
+        if found.len() > limit {
+            return None;
+        }
+        for n in grid.neighbors(loc) {
+            if grid.is_cell(n) && keep(n, steps + 1) && seen.insert(n) {
+                queue.push_back((n, steps + 1));
+            }
+        }
+    }
+    Some(found)
+}
+
+/// Lines from every planet to the closest few others, with the turns a fleet needs for them.
+fn distances(svg: &mut String, map: &ExportMap, centers: &[Point], cell: f32) {
+    let scale = match export_scale(map.options.girth, centers) {
+        Some(scale) => scale,
+        // all planets on one spot, there is nothing to draw between them
+        None => return,
+    };
+    let mut pairs = Vec::new();
+    for (i, a) in centers.iter().enumerate() {
+        let mut others: Vec<_> = (0..centers.len()).filter(|j| *j != i).collect();
+        others.sort_by(|x, y| a.dist(centers[*x]).total_cmp(&a.dist(centers[*y])));
+        for j in others.into_iter().take(NEIGHBORS) {
+            let pair = (i.min(j), i.max(j));
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+        }
+    }
+
+    writeln!(
+        svg,
+        r#"<g stroke="white" stroke-opacity="0.4" stroke-width="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" fill="white">"#,
+        0.03 * cell,
+        0.25 * cell
+    )
+    .unwrap();
+    for (i, j) in pairs {
+        let (a, b) = (centers[i], centers[j]);
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            a.x, -a.y, b.x, -b.y
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{}" y="{}" stroke="none">{}</text>"#,
+            (a.x + b.x) / 2.,
+            -(a.y + b.y) / 2.,
+            turns(a.dist(b) * scale)
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+}
+
+/// `polygon` as the points of an svg polygon.
+fn points(polygon: &[Point]) -> String {
+    polygon
+        .iter()
+        .map(|p| format!("{},{}", p.x, -p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,268 @@
+//! Random maps that are symmetric between the players.
+//!
+//! The same settings always give the same map, all randomness comes from an `RNG` seeded with
+//! `GeneratorSettings::seed`.
+
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use petname::Petnames;
+use rand::{Rng, SeedableRng};
+
+use crate::geometry::symmetry::{Symmetry, SymmetryKind};
+use crate::geometry::{Grid, Location, Point};
+use crate::planet::{PlanetData, PlanetEvent, Player};
+use crate::utils::rng::RNG;
+
+/// Candidate spots tried for every planet before giving up on filling the map.
+const ATTEMPTS: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorSettings {
+    pub seed: u64,
+    pub players: usize,
+    /// Planets in total, home planets included.
+    pub planets: usize,
+    /// Mirror and point symmetry need two players, rotational symmetry gets one copy for every
+    /// player.
+    pub symmetry: SymmetryKind,
+    /// No two planets are closer than this.
+    pub min_spacing: f32,
+    /// Every planet has another one at most this far away.
+    pub max_spacing: f32,
+    pub home_ships: RangeInclusive<usize>,
+    pub neutral_ships: RangeInclusive<usize>,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            players: 2,
+            planets: 12,
+            symmetry: SymmetryKind::Point,
+            min_spacing: 2.,
+            max_spacing: 5.,
+            home_ships: 100..=100,
+            neutral_ships: 5..=50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// There has to be at least one player.
+    Players(usize),
+    /// Mirrors and point symmetry only work out for two players.
+    Symmetry { players: usize, order: usize },
+    /// Not even the home planets fit.
+    TooFewPlanets,
+    /// `min_spacing` is larger than `max_spacing`, or not positive.
+    Spacing,
+    /// The ship count range of home or neutral planets ends before it starts.
+    ShipRange,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Players(n) => write!(f, "{} players, at least 1 is needed", n),
+            GenerateError::Symmetry { players, order } => {
+                write!(f, "{} players can not share {} copies", players, order)
+            }
+            GenerateError::TooFewPlanets => write!(f, "every player needs a planet"),
+            GenerateError::Spacing => write!(f, "min spacing has to be below max spacing"),
+            GenerateError::ShipRange => write!(f, "the ship counts go from high to low"),
+        }
+    }
+}
+
+impl GeneratorSettings {
+    /// Symmetry the map is built with, around the origin.
+    pub fn symmetry(&self) -> Symmetry {
+        let kind = match self.symmetry {
+            SymmetryKind::Rotational(_) => SymmetryKind::Rotational(self.players as u32),
+            kind => kind,
+        };
+        Symmetry {
+            kind,
+            center: Point::default(),
+        }
+    }
+
+    fn check(&self) -> Result<(), GenerateError> {
+        if self.players == 0 {
+            return Err(GenerateError::Players(self.players));
+        }
+        let order = self.symmetry().order();
+        if order > 1 && order != self.players {
+            return Err(GenerateError::Symmetry {
+                players: self.players,
+                order,
+            });
+        }
+        if self.planets < self.players {
+            return Err(GenerateError::TooFewPlanets);
+        }
+        if !(self.min_spacing > 0. && self.min_spacing <= self.max_spacing) {
+            return Err(GenerateError::Spacing);
+        }
+        if self.home_ships.is_empty() || self.neutral_ships.is_empty() {
+            return Err(GenerateError::ShipRange);
+        }
+        Ok(())
+    }
+}
+
+/// A planet on `loc` and its symmetric copies, each with the index of its copy.
+struct Group {
+    cells: Vec<(Location, usize)>,
+}
+
+/// Generates a map on `grid`, as `PlanetEvent::CreateNamed` events.
+///
+/// Home planets come first, one group of copies at a time. Neutral planets are added until
+/// `planets` is reached or nothing fits anymore, so crowded settings give fewer planets.
+pub fn generate(
+    settings: &GeneratorSettings,
+    grid: &dyn Grid,
+) -> Result<Vec<PlanetEvent>, GenerateError> {
+    settings.check()?;
+
+    let mut rng = RNG::seed_from_u64(settings.seed);
+    let names = Petnames::small();
+    let symmetry = settings.symmetry();
+    let order = symmetry.order();
+    // room for all planets at about the largest spacing
+    let radius = settings.max_spacing * (settings.planets as f32).sqrt();
+
+    let mut map = Map::default();
+    for g in 0..settings.players / order {
+        let group = find_group(
+            grid,
+            &symmetry,
+            settings,
+            radius,
+            &map.placed,
+            true,
+            &mut rng,
+        )
+        .ok_or(GenerateError::TooFewPlanets)?;
+        let ships = rng.gen_range(settings.home_ships.clone());
+        map.add(grid, &names, group, ships, |k| g * order + 1 + k, &mut rng);
+    }
+
+    while map.placed.len() < settings.planets {
+        let group = match find_group(
+            grid,
+            &symmetry,
+            settings,
+            radius,
+            &map.placed,
+            false,
+            &mut rng,
+        ) {
+            Some(group) => group,
+            None => break,
+        };
+        let ships = rng.gen_range(settings.neutral_ships.clone());
+        map.add(grid, &names, group, ships, |_| 0, &mut rng);
+    }
+
+    Ok(map.events)
+}
+
+#[derive(Default)]
+struct Map {
+    placed: Vec<Point>,
+    names: HashSet<String>,
+    events: Vec<PlanetEvent>,
+}
+
+impl Map {
+    /// Creates the planets of `group`, copy `k` belongs to `player(k)`.
+    fn add(
+        &mut self,
+        grid: &dyn Grid,
+        names: &Petnames,
+        group: Group,
+        ship_count: usize,
+        player: impl Fn(usize) -> usize,
+        rng: &mut RNG,
+    ) {
+        for (loc, k) in group.cells {
+            self.placed.push(grid.center(loc));
+            self.events.push(PlanetEvent::CreateNamed {
+                loc,
+                data: PlanetData {
+                    player: Player(player(k)),
+                    name: unique_name(names, &mut self.names, rng),
+                    ship_count,
+                    ..Default::default()
+                },
+            });
+        }
+    }
+}
+
+/// Looks for a cell whose copies all keep their distance to `placed` and each other. Home
+/// planets need a copy for every player, neutral ones can lie on a mirror line or the center.
+fn find_group(
+    grid: &dyn Grid,
+    symmetry: &Symmetry,
+    settings: &GeneratorSettings,
+    radius: f32,
+    placed: &[Point],
+    home: bool,
+    rng: &mut RNG,
+) -> Option<Group> {
+    for _ in 0..ATTEMPTS {
+        let (r, a) = (radius * rng.gen::<f32>().sqrt(), rng.gen_range(0.0..TAU));
+        let loc = match grid.cell_at(Point::new(r * a.cos(), r * a.sin())) {
+            Some(loc) => loc,
+            None => continue,
+        };
+
+        let mut cells = vec![(loc, 0)];
+        cells.extend(symmetry.counterparts(grid, loc));
+        let left = settings.planets - placed.len();
+        if cells.len() > left || (home && cells.len() < symmetry.order()) {
+            continue;
+        }
+
+        let points: Vec<_> = cells.iter().map(|(l, _)| grid.center(*l)).collect();
+        let apart = points.iter().enumerate().all(|(i, p)| {
+            placed
+                .iter()
+                .chain(&points[..i])
+                .all(|q| p.dist(*q) >= settings.min_spacing)
+        });
+        // the first planets have nothing to be close to
+        let close = placed.is_empty()
+            || placed
+                .iter()
+                .any(|q| points[0].dist(*q) <= settings.max_spacing);
+        if apart && close {
+            return Some(Group { cells });
+        }
+    }
+    None
+}
+
+/// A fresh planet name, numbered when the dictionary keeps coming up with used ones.
+fn unique_name(names: &Petnames, used: &mut HashSet<String>, rng: &mut RNG) -> String {
+    let mut name = names.generate(rng, 2, " ");
+    let mut i = 2;
+    while used.contains(&name) {
+        if i < 10 {
+            name = names.generate(rng, 2, " ");
+        } else {
+            name = format!("{} {}", names.generate(rng, 2, " "), i);
+        }
+        i += 1;
+    }
+    used.insert(name.clone());
+    name
+}
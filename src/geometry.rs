@@ -0,0 +1,745 @@
+//! Tiling math that does not depend on a running Bevy app.
+//!
+//! Every [`MapType`] has a [`Grid`] implementation that maps cells to world
+//! positions and back. [`crate::map_config::MapConfig`] delegates to these.
+
+use std::collections::{HashSet, VecDeque};
+use std::f32::consts::FRAC_PI_2;
+use std::ops::RangeInclusive;
+
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+pub mod convert;
+pub mod hex;
+pub mod periodic;
+pub mod polar;
+pub mod symmetry;
+
+pub const TRIAG_HEIGHT: f32 = 0.866_025_4; // sqrt(1 - 0.25) height of equal triangle
+const HEX_HEIGHT: f32 = TRIAG_HEIGHT; // half the height of a hexagon with radius 1
+
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub struct Location {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Location {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MapType {
+    Squares,
+    Triangles,
+    Hexagons,
+    Octagons,
+    Rhombille,
+    Cairo,
+    SnubSquare,
+    Free,
+    Polar,
+}
+
+impl MapType {
+    pub const ALL: [MapType; 9] = [
+        MapType::Squares,
+        MapType::Triangles,
+        MapType::Hexagons,
+        MapType::Octagons,
+        MapType::Rhombille,
+        MapType::Cairo,
+        MapType::SnubSquare,
+        MapType::Free,
+        MapType::Polar,
+    ];
+
+    /// The grid with default [`GridParams`].
+    pub fn grid(self) -> Box<dyn Grid + Send + Sync> {
+        self.grid_with(&GridParams::default())
+    }
+
+    pub fn grid_with(self, params: &GridParams) -> Box<dyn Grid + Send + Sync> {
+        let (scale, rotation) = params.transform(self);
+        let affine = Affine::new(scale, rotation);
+        match self {
+            MapType::Squares => Box::new(affine.of(SquareGrid)),
+            MapType::Triangles => Box::new(affine.of(TriangleGrid)),
+            MapType::Hexagons => Box::new(affine.of(HexGrid)),
+            MapType::Octagons => Box::new(affine.of(OctagonGrid {
+                cut: params.octagon_cut,
+            })),
+            MapType::Rhombille => Box::new(affine.of(periodic::RHOMBILLE)),
+            MapType::Cairo => Box::new(affine.of(periodic::CAIRO)),
+            MapType::SnubSquare => Box::new(affine.of(periodic::SNUB_SQUARE)),
+            MapType::Free => Box::new(FreeGrid { snap: params.snap }),
+            MapType::Polar => Box::new(affine.of(polar::PolarGrid {
+                sectors: params.sectors,
+            })),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HexOrientation {
+    /// An edge on top, rows of hexagons run from left to right.
+    #[default]
+    Flat,
+    /// A corner on top, the flat layout turned a quarter.
+    Pointy,
+}
+
+impl HexOrientation {
+    fn rotation(self) -> f32 {
+        match self {
+            HexOrientation::Flat => 0.,
+            HexOrientation::Pointy => FRAC_PI_2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriangleOrientation {
+    /// Triangles point up and down, in rows.
+    #[default]
+    Rows,
+    /// Triangles point left and right, in columns.
+    Columns,
+}
+
+impl TriangleOrientation {
+    fn rotation(self) -> f32 {
+        match self {
+            TriangleOrientation::Rows => 0.,
+            TriangleOrientation::Columns => FRAC_PI_2,
+        }
+    }
+}
+
+/// Shape of the grid on top of the [`MapType`], every map type only looks at some of these.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridParams {
+    /// Scales the cells of every map type but [`MapType::Free`].
+    pub size: f32,
+    pub hex_orientation: HexOrientation,
+    pub triangle_orientation: TriangleOrientation,
+    /// Width over height of [`MapType::Squares`] cells.
+    pub square_aspect: f32,
+    /// See [`OctagonGrid::cut`].
+    pub octagon_cut: f32,
+    /// See [`FreeGrid::snap`].
+    pub snap: Option<f32>,
+    /// See [`polar::PolarGrid::sectors`].
+    pub sectors: u32,
+}
+
+impl GridParams {
+    /// Scale (per axis) and rotation that [`MapType::grid_with`] puts on the plain grid of `ty`.
+    pub fn transform(&self, ty: MapType) -> (Point, f32) {
+        let size = self.size;
+        match ty {
+            MapType::Squares => (Point::new(size * self.square_aspect, size), 0.),
+            MapType::Triangles => (Point::new(size, size), self.triangle_orientation.rotation()),
+            MapType::Hexagons => (Point::new(size, size), self.hex_orientation.rotation()),
+            // positions are already in world units, there is nothing to scale
+            MapType::Free => (Point::new(1., 1.), 0.),
+            _ => (Point::new(size, size), 0.),
+        }
+    }
+}
+
+impl Default for GridParams {
+    fn default() -> Self {
+        Self {
+            size: 1.,
+            hex_orientation: HexOrientation::default(),
+            triangle_orientation: TriangleOrientation::default(),
+            square_aspect: 1.,
+            octagon_cut: OctagonGrid::REGULAR_CUT,
+            snap: None,
+            sectors: polar::PolarGrid::default().sectors,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dist(self, other: Point) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+pub trait Grid {
+    /// World position of the center of `loc`.
+    fn center(&self, loc: Location) -> Point;
+
+    /// The cell that contains the world position `p`.
+    fn cell_at(&self, p: Point) -> Option<Location>;
+
+    /// Corners of `loc` in world coordinates, counter clockwise.
+    fn polygon(&self, loc: Location) -> Vec<Point>;
+
+    /// Cells that share an edge with `loc`.
+    fn neighbors(&self, loc: Location) -> Vec<Location>;
+
+    /// Number of steps from `a` to `b`, every step moves to one of the `neighbors`.
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        let mut seen = HashSet::from([a]);
+        let mut queue = VecDeque::from([(a, 0)]);
+        while let Some((loc, d)) = queue.pop_front() {
+            if loc == b {
+                return d;
+            }
+            for n in self.neighbors(loc) {
+                if seen.insert(n) {
+                    queue.push_back((n, d + 1));
+                }
+            }
+        }
+        u32::MAX
+    }
+
+    /// All cells exactly `radius` steps away from `center`.
+    fn ring(&self, center: Location, radius: u32) -> Vec<Location> {
+        let mut seen = HashSet::from([center]);
+        let mut layer = vec![center];
+        for _ in 0..radius {
+            layer = layer
+                .into_iter()
+                .flat_map(|loc| self.neighbors(loc))
+                .filter(|n| seen.insert(*n))
+                .collect();
+        }
+        layer
+    }
+
+    /// Cells with the same `variant` have the same polygon relative to their center.
+    fn variant(&self, _loc: Location) -> usize {
+        0
+    }
+
+    /// One cell for every variant, indexed by variant.
+    fn prototypes(&self) -> Vec<Location> {
+        vec![Location::default()]
+    }
+
+    /// Angle (counter clockwise, in radians) that the polygon of the prototype for this
+    /// variant is turned around its center to line up with `loc`.
+    fn rotation(&self, _loc: Location) -> f32 {
+        0.
+    }
+
+    /// Whether `loc` is part of the grid at all, grids that cover the plane accept everything.
+    fn is_cell(&self, _loc: Location) -> bool {
+        true
+    }
+}
+
+fn regular_polygon(center: Point, radius: f32, sides: usize, rotation: f32) -> Vec<Point> {
+    (0..sides)
+        .map(|i| {
+            let a = rotation + std::f32::consts::TAU * i as f32 / sides as f32;
+            Point::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+        })
+        .collect()
+}
+
+fn is_odd(v: i32) -> bool {
+    v.rem_euclid(2) == 1
+}
+
+pub struct SquareGrid;
+
+impl Grid for SquareGrid {
+    fn center(&self, loc: Location) -> Point {
+        Point::new(loc.x as f32, loc.y as f32)
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        Some(Location::new(
+            (p.x + 0.5).floor() as i32,
+            (p.y + 0.5).floor() as i32,
+        ))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        let c = self.center(loc);
+        vec![
+            Point::new(c.x - 0.5, c.y - 0.5),
+            Point::new(c.x + 0.5, c.y - 0.5),
+            Point::new(c.x + 0.5, c.y + 0.5),
+            Point::new(c.x - 0.5, c.y + 0.5),
+        ]
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        let Location { x, y } = loc;
+        vec![
+            Location::new(x + 1, y),
+            Location::new(x, y + 1),
+            Location::new(x - 1, y),
+            Location::new(x, y - 1),
+        ]
+    }
+
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+    }
+
+    fn ring(&self, center: Location, radius: u32) -> Vec<Location> {
+        if radius == 0 {
+            return vec![center];
+        }
+
+        let r = radius as i32;
+        (0..r)
+            .flat_map(|i| {
+                [
+                    Location::new(center.x + r - i, center.y + i),
+                    Location::new(center.x - i, center.y + r - i),
+                    Location::new(center.x - r + i, center.y - i),
+                    Location::new(center.x + i, center.y - r + i),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Triangles alternate pointing up (even `x`) and down (odd `x`), even rows are shifted left.
+pub struct TriangleGrid;
+
+impl TriangleGrid {
+    fn row_shift(y: i32) -> i32 {
+        if y % 2 == 0 {
+            -1
+        } else {
+            0
+        }
+    }
+
+    fn points_up(loc: Location) -> bool {
+        !is_odd(loc.x)
+    }
+
+    /// Index of the strip between grid lines in each of the three line directions.
+    /// Moving to a neighbor crosses exactly one line, so distances add up per direction.
+    fn strips(&self, loc: Location) -> [i32; 3] {
+        let c = self.center(loc);
+        let y = if Self::points_up(loc) {
+            c.y - TRIAG_HEIGHT / 6.
+        } else {
+            c.y + TRIAG_HEIGHT / 6.
+        };
+
+        let rising = (TRIAG_HEIGHT * c.x - 0.5 * y) / TRIAG_HEIGHT - 0.25;
+        let falling = (TRIAG_HEIGHT * c.x + 0.5 * y) / TRIAG_HEIGHT + 0.25;
+        [loc.y, rising.floor() as i32, falling.floor() as i32]
+    }
+}
+
+impl Grid for TriangleGrid {
+    fn center(&self, loc: Location) -> Point {
+        Point::new(
+            (loc.x + Self::row_shift(loc.y)) as f32 * 0.5,
+            loc.y as f32 * TRIAG_HEIGHT,
+        )
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        let row = (p.y / TRIAG_HEIGHT + 0.5).floor() as i32;
+        // height inside the row, 0 at the bottom and 1 at the top
+        let t = p.y / TRIAG_HEIGHT + 0.5 - row as f32;
+
+        // in units where the center of cell x sits at q = x
+        let q = p.x * 2.0 - Self::row_shift(row) as f32;
+        let col = q.floor() as i32;
+        let f = q - col as f32;
+
+        let edge = if is_odd(col) { t } else { 1.0 - t };
+        let x = if f <= edge { col } else { col + 1 };
+
+        Some(Location::new(x, row))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        let c = self.center(loc);
+        let th2 = TRIAG_HEIGHT * 0.5;
+        if Self::points_up(loc) {
+            vec![
+                Point::new(c.x - 0.5, c.y - th2),
+                Point::new(c.x + 0.5, c.y - th2),
+                Point::new(c.x, c.y + th2),
+            ]
+        } else {
+            vec![
+                Point::new(c.x + 0.5, c.y + th2),
+                Point::new(c.x - 0.5, c.y + th2),
+                Point::new(c.x, c.y - th2),
+            ]
+        }
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        let Location { x, y } = loc;
+        let dy = if Self::points_up(loc) { -1 } else { 1 };
+        let dx = Self::row_shift(y) - Self::row_shift(y + dy);
+        vec![
+            Location::new(x + 1, y),
+            Location::new(x - 1, y),
+            Location::new(x + dx, y + dy),
+        ]
+    }
+
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        let (a, b) = (self.strips(a), self.strips(b));
+        (0..3).map(|i| a[i].abs_diff(b[i])).sum()
+    }
+
+    fn variant(&self, loc: Location) -> usize {
+        if Self::points_up(loc) {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn prototypes(&self) -> Vec<Location> {
+        vec![Location::new(0, 0), Location::new(1, 0)]
+    }
+}
+
+/// Flat topped hexagons with radius 1, odd rows sit half a column to the right.
+pub struct HexGrid;
+
+impl Grid for HexGrid {
+    fn center(&self, loc: Location) -> Point {
+        let mut x = loc.x as f32 * 3.0;
+        let y = loc.y as f32 * HEX_HEIGHT;
+
+        if is_odd(loc.y) {
+            x += 1.5;
+        }
+
+        Point::new(x, y)
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        let q = p.x * 2. / 3.;
+        let r = -p.x / 3. + p.y / (2. * HEX_HEIGHT);
+        Some(Location::from_cube(hex::Cube::round(q, r)))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        regular_polygon(self.center(loc), 1.0, 6, 0.)
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        hex::neighbors(loc)
+    }
+
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        hex::hex_distance(a, b)
+    }
+
+    fn ring(&self, center: Location, radius: u32) -> Vec<Location> {
+        hex::ring(center, radius)
+    }
+}
+
+/// Octagons on even rows, the small squares between them on odd rows.
+pub struct OctagonGrid {
+    /// How much of the unit cell is cut off at every corner of an octagon, along each axis.
+    /// This is also half the diagonal of the small squares.
+    pub cut: f32,
+}
+
+impl OctagonGrid {
+    /// Cut that makes the octagons regular, (1 - tan(pi / 8)) / 2.
+    pub const REGULAR_CUT: f32 = 0.292_893_2;
+    /// Cuts that still leave both octagons and squares.
+    pub const CUTS: RangeInclusive<f32> = 0.05..=0.45;
+
+    fn cut(&self) -> f32 {
+        self.cut.clamp(*Self::CUTS.start(), *Self::CUTS.end())
+    }
+
+    /// Octagons (on a plain square lattice) touching `loc`, with the steps needed to get there.
+    fn anchors(loc: Location) -> Vec<((i32, i32), u32)> {
+        if is_odd(loc.y) {
+            let j = loc.y.div_euclid(2);
+            vec![
+                ((loc.x, j), 1),
+                ((loc.x + 1, j), 1),
+                ((loc.x, j + 1), 1),
+                ((loc.x + 1, j + 1), 1),
+            ]
+        } else {
+            vec![((loc.x, loc.y / 2), 0)]
+        }
+    }
+}
+
+impl Grid for OctagonGrid {
+    fn center(&self, loc: Location) -> Point {
+        let mut x = loc.x as f32 + 0.5;
+        let y = loc.y as f32 * 0.5 + 0.5;
+
+        if is_odd(loc.y) {
+            x += 0.5;
+        }
+
+        Point::new(x, y)
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        let i = p.x.floor();
+        let j = p.y.floor();
+        let dx = p.x - i - 0.5;
+        let dy = p.y - j - 0.5;
+        let (i, j) = (i as i32, j as i32);
+
+        if dx.abs() + dy.abs() <= 1. - self.cut() {
+            return Some(Location::new(i, 2 * j));
+        }
+
+        let x = if dx > 0. { i } else { i - 1 };
+        let y = if dy > 0. { 2 * j + 1 } else { 2 * j - 1 };
+        Some(Location::new(x, y))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        let c = self.center(loc);
+        let cut = self.cut();
+        if is_odd(loc.y) {
+            return regular_polygon(c, cut, 4, 0.);
+        }
+
+        let (a, b) = (0.5, 0.5 - cut);
+        [
+            (a, -b),
+            (a, b),
+            (b, a),
+            (-b, a),
+            (-a, b),
+            (-a, -b),
+            (-b, -a),
+            (b, -a),
+        ]
+        .into_iter()
+        .map(|(x, y)| Point::new(c.x + x, c.y + y))
+        .collect()
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        let Location { x, y } = loc;
+        if is_odd(y) {
+            vec![
+                Location::new(x + 1, y + 1),
+                Location::new(x, y + 1),
+                Location::new(x, y - 1),
+                Location::new(x + 1, y - 1),
+            ]
+        } else {
+            vec![
+                Location::new(x + 1, y),
+                Location::new(x, y + 1),
+                Location::new(x, y + 2),
+                Location::new(x - 1, y + 1),
+                Location::new(x - 1, y),
+                Location::new(x - 1, y - 1),
+                Location::new(x, y - 2),
+                Location::new(x, y - 1),
+            ]
+        }
+    }
+
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        if a == b {
+            return 0;
+        }
+
+        // going through a small square never beats walking over octagons
+        let anchors = Self::anchors(b);
+        Self::anchors(a)
+            .into_iter()
+            .flat_map(|(p, cost)| {
+                anchors
+                    .iter()
+                    .map(move |(q, cost2)| cost + cost2 + p.0.abs_diff(q.0) + p.1.abs_diff(q.1))
+            })
+            .min()
+            .unwrap()
+    }
+
+    fn variant(&self, loc: Location) -> usize {
+        if is_odd(loc.y) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn prototypes(&self) -> Vec<Location> {
+        vec![Location::new(0, 0), Location::new(0, 1)]
+    }
+}
+
+/// Gridless placement, a location is just a world position in fixed point.
+///
+/// `x` and `y` count steps of `1 / FreeGrid::RESOLUTION` world units, so planets can sit
+/// anywhere while still being keyed by a [`Location`]. Free cells never touch each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FreeGrid {
+    /// Spacing in world units that picked positions are rounded to.
+    pub snap: Option<f32>,
+}
+
+impl FreeGrid {
+    pub const RESOLUTION: f32 = 1000.;
+    const RADIUS: f32 = 0.4; // size of the disk that is drawn for a planet
+
+    fn snapped(&self, v: f32) -> f32 {
+        match self.snap {
+            Some(spacing) if spacing > 0. => (v / spacing).round() * spacing,
+            _ => v,
+        }
+    }
+}
+
+impl Grid for FreeGrid {
+    fn center(&self, loc: Location) -> Point {
+        Point::new(
+            loc.x as f32 / Self::RESOLUTION,
+            loc.y as f32 / Self::RESOLUTION,
+        )
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        Some(Location::new(
+            (self.snapped(p.x) * Self::RESOLUTION).round() as i32,
+            (self.snapped(p.y) * Self::RESOLUTION).round() as i32,
+        ))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        regular_polygon(self.center(loc), Self::RADIUS, 24, 0.)
+    }
+
+    fn neighbors(&self, _loc: Location) -> Vec<Location> {
+        Vec::new()
+    }
+}
+
+/// Another grid scaled along the axes and then turned counter clockwise around the origin.
+///
+/// Only scale both axes the same when the inner grid turns its cells (see [`Grid::rotation`]).
+pub struct Affine<G> {
+    inner: G,
+    scale: Point,
+    rotation: f32,
+}
+
+impl Affine<()> {
+    pub fn new(scale: Point, rotation: f32) -> Self {
+        Self {
+            inner: (),
+            scale,
+            rotation,
+        }
+    }
+
+    pub fn of<G: Grid>(self, inner: G) -> Affine<G> {
+        Affine {
+            inner,
+            scale: self.scale,
+            rotation: self.rotation,
+        }
+    }
+}
+
+impl<G> Affine<G> {
+    fn to_world(&self, p: Point) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (p.x * self.scale.x, p.y * self.scale.y);
+        Point::new(x * cos - y * sin, x * sin + y * cos)
+    }
+
+    fn to_inner(&self, p: Point) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (p.x * cos + p.y * sin, -p.x * sin + p.y * cos);
+        Point::new(x / self.scale.x, y / self.scale.y)
+    }
+}
+
+impl<G: Grid> Grid for Affine<G> {
+    fn center(&self, loc: Location) -> Point {
+        self.to_world(self.inner.center(loc))
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        self.inner.cell_at(self.to_inner(p))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        self.inner
+            .polygon(loc)
+            .into_iter()
+            .map(|p| self.to_world(p))
+            .collect()
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        self.inner.neighbors(loc)
+    }
+
+    fn distance(&self, a: Location, b: Location) -> u32 {
+        self.inner.distance(a, b)
+    }
+
+    fn ring(&self, center: Location, radius: u32) -> Vec<Location> {
+        self.inner.ring(center, radius)
+    }
+
+    fn variant(&self, loc: Location) -> usize {
+        self.inner.variant(loc)
+    }
+
+    fn prototypes(&self) -> Vec<Location> {
+        self.inner.prototypes()
+    }
+
+    fn rotation(&self, loc: Location) -> f32 {
+        // prototypes are turned as well, so this stays relative to them
+        self.inner.rotation(loc)
+    }
+
+    fn is_cell(&self, loc: Location) -> bool {
+        self.inner.is_cell(loc)
+    }
+}
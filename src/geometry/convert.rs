@@ -0,0 +1,107 @@
+//! Moving cells from one grid to another while keeping their world position.
+
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+
+use super::{Grid, Location, Point};
+
+/// Where a location of the old grid ends up in the new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub to: Location,
+    /// The nearest cell, when another location got there first. `to` is then the closest free
+    /// cell, or the taken cell itself when nothing is free close by.
+    pub taken: Option<Location>,
+}
+
+/// Distance between the circles that are searched for a free cell.
+const SEARCH_STEP: f32 = 0.25;
+/// Give up looking for a free cell this far away from the original position.
+const SEARCH_RADIUS: f32 = 10.;
+
+/// Maps every location to the cell of `to` under its center in `from`. Locations that end up
+/// on the same cell are spread over the nearest free cells, the one closest to the shared cell
+/// keeps it.
+pub fn relocate(from: &dyn Grid, to: &dyn Grid, locations: &[Location]) -> Vec<Placement> {
+    let points: Vec<_> = locations.iter().map(|l| from.center(*l)).collect();
+    place(to, &points)
+        .into_iter()
+        .zip(locations)
+        // nothing of the new grid is close, leave it where it is
+        .map(|(placement, loc)| {
+            placement.unwrap_or(Placement {
+                to: *loc,
+                taken: Some(*loc),
+            })
+        })
+        .collect()
+}
+
+/// Like [`relocate`], for world positions. `None` when no cell of `to` is close to a point.
+pub fn place(to: &dyn Grid, points: &[Point]) -> Vec<Option<Placement>> {
+    let wanted: Vec<_> = points
+        .iter()
+        .map(|p| to.cell_at(*p).or_else(|| nearest(to, *p, |_| true)))
+        .collect();
+
+    let off = |i: usize| match wanted[i] {
+        Some(loc) => to.center(loc).dist(points[i]),
+        None => f32::MAX,
+    };
+    let mut order: Vec<_> = (0..points.len()).collect();
+    order.sort_by(|a, b| off(*a).total_cmp(&off(*b)));
+
+    // first every cell goes to the closest point that wants it, so the ones that have to move
+    // can not push others out of their own cell
+    let mut used = HashSet::new();
+    let mut out = vec![None; points.len()];
+    for &i in &order {
+        if let Some(loc) = wanted[i].filter(|l| used.insert(*l)) {
+            out[i] = Some(Placement {
+                to: loc,
+                taken: None,
+            });
+        }
+    }
+
+    for i in order {
+        if out[i].is_some() {
+            continue;
+        }
+        if let Some(loc) = wanted[i] {
+            let placement = Placement {
+                to: nearest(to, points[i], |l| !used.contains(&l)).unwrap_or(loc),
+                taken: Some(loc),
+            };
+            used.insert(placement.to);
+            out[i] = Some(placement);
+        }
+    }
+    out
+}
+
+/// Cell of `grid` closest to `p` that passes `free`, looking at growing circles around `p`.
+fn nearest(grid: &dyn Grid, p: Point, free: impl Fn(Location) -> bool) -> Option<Location> {
+    let mut best: Option<(f32, Location)> = None;
+    let mut radius = SEARCH_STEP;
+    while radius <= SEARCH_RADIUS {
+        let samples = (TAU * radius / SEARCH_STEP).ceil() as usize;
+        for i in 0..samples {
+            let a = TAU * i as f32 / samples as f32;
+            let sample = Point::new(p.x + radius * a.cos(), p.y + radius * a.sin());
+            if let Some(loc) = grid.cell_at(sample).filter(|l| free(*l)) {
+                let d = grid.center(loc).dist(p);
+                if !matches!(best, Some((bd, _)) if bd <= d) {
+                    best = Some((d, loc));
+                }
+            }
+        }
+
+        // cells found later are centered at least `radius` minus a cell size away
+        if matches!(best, Some((d, _)) if d < radius - 1.) {
+            break;
+        }
+        radius += SEARCH_STEP;
+    }
+    best.map(|(_, loc)| loc)
+}
@@ -0,0 +1,157 @@
+//! Axial and cube coordinates for [`MapType::Hexagons`](super::MapType::Hexagons).
+//!
+//! The editor stores hexagons as a [`Location`] where `y` counts half rows and odd half rows
+//! are shifted to the right. Neighbor and distance math is a lot simpler in axial (`q`, `r`)
+//! or cube (`q`, `r`, `s` with `q + r + s == 0`) coordinates, see
+//! <https://www.redblobgames.com/grids/hexagons/>.
+
+use super::Location;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Axial {
+    pub q: i32,
+    pub r: i32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cube {
+    pub q: i32,
+    pub r: i32,
+    pub s: i32,
+}
+
+/// Axial directions, counter clockwise starting at the upper neighbor.
+pub const DIRECTIONS: [Axial; 6] = [
+    Axial { q: 0, r: 1 },
+    Axial { q: -1, r: 1 },
+    Axial { q: -1, r: 0 },
+    Axial { q: 0, r: -1 },
+    Axial { q: 1, r: -1 },
+    Axial { q: 1, r: 0 },
+];
+
+impl Axial {
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    pub fn to_cube(self) -> Cube {
+        Cube {
+            q: self.q,
+            r: self.r,
+            s: -self.q - self.r,
+        }
+    }
+
+    pub fn distance(self, other: Axial) -> u32 {
+        self.to_cube().distance(other.to_cube())
+    }
+
+    fn add(self, other: Axial) -> Axial {
+        Axial::new(self.q + other.q, self.r + other.r)
+    }
+
+    fn scale(self, k: i32) -> Axial {
+        Axial::new(self.q * k, self.r * k)
+    }
+}
+
+impl Cube {
+    pub fn to_axial(self) -> Axial {
+        Axial::new(self.q, self.r)
+    }
+
+    pub fn distance(self, other: Cube) -> u32 {
+        ((self.q - other.q).abs())
+            .max((self.r - other.r).abs())
+            .max((self.s - other.s).abs()) as u32
+    }
+
+    /// Rounds fractional cube coordinates to the cube that contains them.
+    pub fn round(q: f32, r: f32) -> Cube {
+        let s = -q - r;
+        let (mut rq, mut rr, mut rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        } else {
+            rs = -rq - rr;
+        }
+
+        Cube {
+            q: rq as i32,
+            r: rr as i32,
+            s: rs as i32,
+        }
+    }
+}
+
+impl Location {
+    pub fn to_axial(self) -> Axial {
+        let q = 2 * self.x + self.y.rem_euclid(2);
+        Axial::new(q, (self.y - q) / 2)
+    }
+
+    pub fn from_axial(axial: Axial) -> Self {
+        Location::new(axial.q.div_euclid(2), 2 * axial.r + axial.q)
+    }
+
+    pub fn to_cube(self) -> Cube {
+        self.to_axial().to_cube()
+    }
+
+    pub fn from_cube(cube: Cube) -> Self {
+        Self::from_axial(cube.to_axial())
+    }
+}
+
+pub fn neighbors(loc: Location) -> Vec<Location> {
+    let axial = loc.to_axial();
+    DIRECTIONS
+        .iter()
+        .map(|d| Location::from_axial(axial.add(*d)))
+        .collect()
+}
+
+/// All cells at exactly distance `radius` from `center`, counter clockwise.
+pub fn ring(center: Location, radius: u32) -> Vec<Location> {
+    if radius == 0 {
+        return vec![center];
+    }
+
+    let radius = radius as i32;
+    let mut current = center.to_axial().add(DIRECTIONS[4].scale(radius));
+    let mut out = Vec::with_capacity(6 * radius as usize);
+    for dir in DIRECTIONS {
+        for _ in 0..radius {
+            out.push(Location::from_axial(current));
+            current = current.add(dir);
+        }
+    }
+    out
+}
+
+pub fn hex_distance(a: Location, b: Location) -> u32 {
+    a.to_axial().distance(b.to_axial())
+}
+
+/// The cells on the straight line from `a` to `b`, both included.
+pub fn line(a: Location, b: Location) -> Vec<Location> {
+    let (a, b) = (a.to_cube(), b.to_cube());
+    let n = a.distance(b);
+    if n == 0 {
+        return vec![Location::from_cube(a)];
+    }
+
+    // nudge the line a bit so it never runs exactly over an edge
+    let (aq, ar) = (a.q as f32 + 1e-4, a.r as f32 + 1e-4);
+    let (bq, br) = (b.q as f32 + 1e-4, b.r as f32 + 1e-4);
+    (0..=n)
+        .map(|i| {
+            let t = i as f32 / n as f32;
+            Location::from_cube(Cube::round(aq + (bq - aq) * t, ar + (br - ar) * t))
+        })
+        .collect()
+}
@@ -0,0 +1,183 @@
+//! Tilings that repeat a handful of tiles along two lattice vectors.
+//!
+//! Cell `Location { x, y }` is tile `x mod n` of the copy of the unit cell at
+//! `a * (x div n) + b * y`, where `n` is the number of tiles per unit cell.
+//! Tiles are stored as lists of vertex ids, so neighbors are found by exact shared edges.
+
+use super::{Grid, Location, Point};
+
+/// A corner of a tile: index into `vertices` and the unit cell it belongs to, relative to the tile.
+type Corner = (usize, i32, i32);
+
+#[derive(Clone, Copy)]
+pub struct PeriodicGrid {
+    a: Point,
+    b: Point,
+    vertices: &'static [Point],
+    tiles: &'static [&'static [Corner]],
+}
+
+const fn p(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+
+/// Every hexagon of a (pointy topped, radius 1) hexagonal grid split into three rhombi.
+pub const RHOMBILLE: PeriodicGrid = PeriodicGrid {
+    a: p(1.732_050_8, 0.),
+    b: p(0.866_025_4, 1.5),
+    vertices: &[p(0., 0.), p(0., 1.), p(0.866_025_4, 0.5)],
+    tiles: &[
+        &[(0, 0, 0), (2, 0, 0), (1, 0, 0), (2, -1, 0)],
+        &[(0, 0, 0), (2, -1, 0), (1, 0, -1), (2, 0, -1)],
+        &[(0, 0, 0), (2, 0, -1), (1, 1, -1), (2, 0, 0)],
+    ],
+};
+
+/// Snub square tiling with edges of length 1, squares are turned 15 degrees either way.
+pub const SNUB_SQUARE: PeriodicGrid = PeriodicGrid {
+    a: p(1.931_851_7, 0.),
+    b: p(0., 1.931_851_7),
+    vertices: &[
+        p(0.353_553_4, 0.612_372_4),
+        p(1.319_479_2, 0.353_553_4),
+        p(1.578_298_3, 1.319_479_2),
+        p(0.612_372_4, 1.578_298_3),
+    ],
+    tiles: &[
+        &[(2, -1, -1), (3, 0, -1), (0, 0, 0), (1, -1, 0)],
+        &[(0, 0, 0), (1, 0, 0), (2, 0, 0), (3, 0, 0)],
+        &[(3, 0, -1), (1, 0, 0), (0, 0, 0)],
+        &[(0, 0, 0), (3, 0, 0), (2, -1, 0)],
+        &[(1, 0, 0), (0, 1, 0), (2, 0, 0)],
+        &[(3, 0, 0), (2, 0, 0), (1, 0, 1)],
+    ],
+};
+
+/// Cairo pentagonal tiling, the dual of [`SNUB_SQUARE`]: its corners are the centers of the
+/// snub square tiles and every snub square vertex becomes a pentagon.
+pub const CAIRO: PeriodicGrid = PeriodicGrid {
+    a: p(1.931_851_7, 0.),
+    b: p(0., 1.931_851_7),
+    vertices: &[
+        p(0.761_801_7, 0.204_124_1),
+        p(0.204_124_1, 1.170_05),
+        p(1.727_727_5, 0.761_801_7),
+        p(1.170_05, 1.727_727_5),
+        p(0., 0.),
+        p(0.965_925_8, 0.965_925_8),
+    ],
+    tiles: &[
+        &[(4, 0, 0), (0, 0, 0), (5, 0, 0), (1, 0, 0), (2, -1, 0)],
+        &[(0, 0, 0), (3, 0, -1), (4, 1, 0), (2, 0, 0), (5, 0, 0)],
+        &[(5, 0, 0), (2, 0, 0), (1, 1, 0), (4, 1, 1), (3, 0, 0)],
+        &[(1, 0, 0), (5, 0, 0), (3, 0, 0), (0, 0, 1), (4, 0, 1)],
+    ],
+};
+
+impl PeriodicGrid {
+    /// Splits `loc` into its tile index and unit cell.
+    fn split(&self, loc: Location) -> (usize, i32, i32) {
+        let n = self.tiles.len() as i32;
+        (loc.x.rem_euclid(n) as usize, loc.x.div_euclid(n), loc.y)
+    }
+
+    fn location(&self, tile: usize, i: i32, j: i32) -> Location {
+        Location::new(i * self.tiles.len() as i32 + tile as i32, j)
+    }
+
+    fn corners(&self, loc: Location) -> impl Iterator<Item = Corner> + '_ {
+        let (tile, i, j) = self.split(loc);
+        self.tiles[tile]
+            .iter()
+            .map(move |(v, di, dj)| (*v, i + di, j + dj))
+    }
+
+    fn position(&self, (v, i, j): Corner) -> Point {
+        let v = self.vertices[v];
+        p(
+            v.x + self.a.x * i as f32 + self.b.x * j as f32,
+            v.y + self.a.y * i as f32 + self.b.y * j as f32,
+        )
+    }
+
+    /// Unit cell containing `p` when the unit cells are parallelograms at the origin.
+    fn unit_cell(&self, p: Point) -> (i32, i32) {
+        let det = self.a.x * self.b.y - self.a.y * self.b.x;
+        let u = (p.x * self.b.y - p.y * self.b.x) / det;
+        let v = (self.a.x * p.y - self.a.y * p.x) / det;
+        (u.floor() as i32, v.floor() as i32)
+    }
+
+    /// All cells of the unit cells at most `r` away from `(i, j)`.
+    fn nearby(&self, (i, j): (i32, i32), r: i32) -> impl Iterator<Item = Location> + '_ {
+        (i - r..=i + r).flat_map(move |i| {
+            (j - r..=j + r)
+                .flat_map(move |j| (0..self.tiles.len()).map(move |t| self.location(t, i, j)))
+        })
+    }
+
+    fn contains(&self, loc: Location, p: Point) -> bool {
+        let polygon = self.polygon(loc);
+        (0..polygon.len()).all(|k| {
+            let (a, b) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+            (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x) >= 0.
+        })
+    }
+}
+
+impl Grid for PeriodicGrid {
+    fn center(&self, loc: Location) -> Point {
+        let corners: Vec<_> = self.corners(loc).map(|c| self.position(c)).collect();
+        let n = corners.len() as f32;
+        p(
+            corners.iter().map(|c| c.x).sum::<f32>() / n,
+            corners.iter().map(|c| c.y).sum::<f32>() / n,
+        )
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        // tiles reach at most one unit cell away from their own
+        let mut nearby: Vec<_> = self.nearby(self.unit_cell(p), 1).collect();
+        if let Some(loc) = nearby.iter().find(|loc| self.contains(**loc, p)) {
+            return Some(*loc);
+        }
+
+        // only on an edge, where float noise puts the point outside of both tiles
+        nearby.sort_by(|a, b| {
+            let da = self.center(*a).dist(p);
+            let db = self.center(*b).dist(p);
+            da.total_cmp(&db)
+        });
+        nearby.first().copied()
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        self.corners(loc).map(|c| self.position(c)).collect()
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        let corners: Vec<_> = self.corners(loc).collect();
+        let edges: Vec<_> = (0..corners.len())
+            .map(|k| (corners[(k + 1) % corners.len()], corners[k]))
+            .collect();
+
+        let (_, i, j) = self.split(loc);
+        self.nearby((i, j), 2)
+            .filter(|other| *other != loc)
+            .filter(|other| {
+                let other: Vec<_> = self.corners(*other).collect();
+                (0..other.len()).any(|k| edges.contains(&(other[k], other[(k + 1) % other.len()])))
+            })
+            .collect()
+    }
+
+    fn variant(&self, loc: Location) -> usize {
+        self.split(loc).0
+    }
+
+    fn prototypes(&self) -> Vec<Location> {
+        (0..self.tiles.len())
+            .map(|t| self.location(t, 0, 0))
+            .collect()
+    }
+}
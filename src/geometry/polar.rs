@@ -0,0 +1,150 @@
+//! Rings around the origin, split into sectors.
+//!
+//! Cell `Location { x, y }` is sector `x` of ring `y`. Ring 0 is the disk in the middle, ring
+//! `r` is `1` wide around it and has `sectors * r` sectors so cells keep about the same size.
+//! Sector 0 starts at the positive x axis and sectors count counter clockwise.
+
+use std::f32::consts::TAU;
+
+use super::{Grid, Location, Point};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolarGrid {
+    /// Sectors in the first ring, ring `r` has `r` times as many.
+    pub sectors: u32,
+}
+
+impl Default for PolarGrid {
+    fn default() -> Self {
+        Self { sectors: 6 }
+    }
+}
+
+impl PolarGrid {
+    /// Outermost ring, everything further out is off the map.
+    pub const RINGS: i32 = 64;
+    /// Fewer sectors make cells in the first ring too bent to draw as a fan.
+    pub const MIN_SECTORS: u32 = 4;
+    /// Longest straight piece used to draw an arc.
+    const ARC_STEP: f32 = 0.05;
+
+    /// Number of cells in `ring`.
+    pub fn sectors_in(&self, ring: i32) -> i32 {
+        if ring == 0 {
+            1
+        } else {
+            self.sectors.max(Self::MIN_SECTORS) as i32 * ring
+        }
+    }
+
+    /// Angle that one sector of `ring` spans.
+    fn sector_angle(&self, ring: i32) -> f32 {
+        TAU / self.sectors_in(ring) as f32
+    }
+
+    fn point(radius: f32, angle: f32) -> Point {
+        Point::new(radius * angle.cos(), radius * angle.sin())
+    }
+
+    /// Points on the arc with `radius` from angle `from` to `to`, both included.
+    fn arc(radius: f32, from: f32, to: f32) -> impl Iterator<Item = Point> {
+        let steps = ((to - from).abs() * radius / Self::ARC_STEP).ceil().max(1.) as usize;
+        (0..=steps).map(move |i| Self::point(radius, from + (to - from) * i as f32 / steps as f32))
+    }
+
+    /// Sectors of `ring` that share a piece of arc with sector `x` of ring `ring ± 1`.
+    fn overlapping(&self, x: i32, from_ring: i32, ring: i32) -> impl Iterator<Item = Location> {
+        let (n, m) = (self.sectors_in(from_ring), self.sectors_in(ring));
+        // sector j of `ring` spans [j / m, (j + 1) / m) turns, compare in whole numbers
+        let first = (x * m).div_euclid(n);
+        (first..=first + m / n + 1)
+            .filter(move |j| j * n < (x + 1) * m && (j + 1) * n > x * m)
+            .map(move |j| Location::new(j, ring))
+    }
+}
+
+impl Grid for PolarGrid {
+    fn center(&self, loc: Location) -> Point {
+        if loc.y == 0 {
+            return Point::new(0., 0.);
+        }
+        Self::point(
+            loc.y as f32,
+            (loc.x as f32 + 0.5) * self.sector_angle(loc.y),
+        )
+    }
+
+    fn cell_at(&self, p: Point) -> Option<Location> {
+        let ring = (p.x.hypot(p.y) + 0.5).floor() as i32;
+        if ring == 0 {
+            return Some(Location::new(0, 0));
+        }
+        if ring > Self::RINGS {
+            return None;
+        }
+
+        let angle = p.y.atan2(p.x).rem_euclid(TAU);
+        let n = self.sectors_in(ring);
+        let x = ((angle / self.sector_angle(ring)).floor() as i32).clamp(0, n - 1);
+        Some(Location::new(x, ring))
+    }
+
+    fn polygon(&self, loc: Location) -> Vec<Point> {
+        if loc.y == 0 {
+            let steps = (TAU * 0.5 / Self::ARC_STEP).ceil() as usize;
+            return Self::arc(0.5, 0., TAU).take(steps).collect();
+        }
+
+        let step = self.sector_angle(loc.y);
+        let (from, to) = (loc.x as f32 * step, (loc.x + 1) as f32 * step);
+        let r = loc.y as f32;
+        Self::arc(r + 0.5, from, to)
+            .chain(Self::arc(r - 0.5, to, from))
+            .collect()
+    }
+
+    fn neighbors(&self, loc: Location) -> Vec<Location> {
+        let Location { x, y } = loc;
+        if y == 0 {
+            return (0..self.sectors_in(1))
+                .map(|x| Location::new(x, 1))
+                .collect();
+        }
+
+        let n = self.sectors_in(y);
+        let mut out = vec![
+            Location::new((x + 1).rem_euclid(n), y),
+            Location::new((x - 1).rem_euclid(n), y),
+        ];
+        if y == 1 {
+            out.push(Location::new(0, 0));
+        } else {
+            out.extend(self.overlapping(x, y, y - 1));
+        }
+        if y < Self::RINGS {
+            out.extend(self.overlapping(x, y, y + 1));
+        }
+        out
+    }
+
+    /// Planets kept off the grid by a switch of map type get the mesh of the closest ring.
+    fn variant(&self, loc: Location) -> usize {
+        loc.y.clamp(0, Self::RINGS) as usize
+    }
+
+    fn prototypes(&self) -> Vec<Location> {
+        (0..=Self::RINGS).map(|y| Location::new(0, y)).collect()
+    }
+
+    fn rotation(&self, loc: Location) -> f32 {
+        if loc.y == 0 {
+            0.
+        } else {
+            loc.x as f32 * self.sector_angle(loc.y)
+        }
+    }
+
+    fn is_cell(&self, loc: Location) -> bool {
+        (0..=Self::RINGS).contains(&loc.y) && (0..self.sectors_in(loc.y)).contains(&loc.x)
+    }
+}
@@ -0,0 +1,84 @@
+//! Mirror and rotational symmetry, to place a planet and all of its counterparts at once.
+
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Grid, Location, Point};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymmetryKind {
+    #[default]
+    None,
+    /// Left and right mirror each other, over the vertical line through the center.
+    Horizontal,
+    /// Top and bottom mirror each other, over the horizontal line through the center.
+    Vertical,
+    /// Half a turn around the center.
+    Point,
+    /// `n` copies, each turned `1 / n` of a full turn further around the center.
+    Rotational(u32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Symmetry {
+    pub kind: SymmetryKind,
+    /// World position that is mirrored over or turned around.
+    pub center: Point,
+}
+
+impl Symmetry {
+    /// Number of copies of every planet, the original included.
+    pub fn order(&self) -> usize {
+        match self.kind {
+            SymmetryKind::None => 1,
+            SymmetryKind::Horizontal | SymmetryKind::Vertical | SymmetryKind::Point => 2,
+            SymmetryKind::Rotational(n) => n.max(1) as usize,
+        }
+    }
+
+    /// Where copy `k` (`1..order()`) of `p` goes.
+    pub fn image(&self, p: Point, k: usize) -> Point {
+        let c = self.center;
+        let (dx, dy) = (p.x - c.x, p.y - c.y);
+        match self.kind {
+            SymmetryKind::None => p,
+            SymmetryKind::Horizontal => Point::new(c.x - dx, p.y),
+            SymmetryKind::Vertical => Point::new(p.x, c.y - dy),
+            SymmetryKind::Point => Point::new(c.x - dx, c.y - dy),
+            SymmetryKind::Rotational(n) => {
+                let (sin, cos) = (TAU * k as f32 / n as f32).sin_cos();
+                Point::new(c.x + dx * cos - dy * sin, c.y + dx * sin + dy * cos)
+            }
+        }
+    }
+
+    /// Owner of copy `k`: the first `order()` players take turns, neutral planets and players
+    /// past that keep their owner. For a mirror this swaps player 1 and 2.
+    pub fn permute(&self, player: usize, k: usize) -> usize {
+        let n = self.order();
+        if player == 0 || player > n {
+            player
+        } else {
+            (player - 1 + k) % n + 1
+        }
+    }
+
+    /// The other cells that belong with `loc`, with the index of the copy they are.
+    ///
+    /// Copies that land on `loc` itself (on the mirror line or the center) or on an earlier copy
+    /// are left out, as are copies outside of the grid.
+    pub fn counterparts(&self, grid: &dyn Grid, loc: Location) -> Vec<(Location, usize)> {
+        let p = grid.center(loc);
+        let mut out: Vec<(Location, usize)> = Vec::new();
+        for k in 1..self.order() {
+            if let Some(other) = grid.cell_at(self.image(p, k)) {
+                if other != loc && out.iter().all(|(l, _)| *l != other) {
+                    out.push((other, k));
+                }
+            }
+        }
+        out
+    }
+}
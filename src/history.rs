@@ -0,0 +1,549 @@
+//! Undo and redo of everything that changes the map.
+//!
+//! Edits are recorded from the `PlanetEvent`s and `MapEvent`s sent during a frame, all events
+//! of one frame make up one step. Planets are found back by their location rather than their
+//! `Entity`, undoing a delete spawns a new entity. Steps are undone and redone strictly in
+//! order, so the map looks exactly like it did right after a step whenever it is undone.
+
+use std::collections::HashMap;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+
+use crate::attributes::{attribute, AttributeValue};
+use crate::geometry::{symmetry::Symmetry, GridParams};
+use crate::map_config::{MapConfig, MapEvent, MapType};
+use crate::planet::{Location, PlanetData, PlanetEvent, Player};
+
+/// Steps kept around, the oldest ones are forgotten first.
+const LIMIT: usize = 200;
+/// Seconds between the edits of one drag or typing burst, later edits start a step of their own.
+const MERGE_WINDOW: f64 = 1.;
+
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+    /// Forgets every step, sent when a load replaces the whole map. The planets it removes are
+    /// not deleted through events, so there is no step that could bring them back.
+    Clear,
+}
+
+pub struct HistoryPlugin;
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        // after `Update`, so the events of the frame are in and despawned planets are gone
+        app.init_resource::<History>()
+            .add_event::<HistoryEvent>()
+            .add_system_to_stage(CoreStage::PostUpdate, track_history);
+    }
+}
+
+type PlanetChanged = Or<(Changed<Location>, Changed<PlanetData>)>;
+
+#[allow(clippy::too_many_arguments)]
+fn track_history(
+    mut history: ResMut<History>,
+    mut requests: EventReader<HistoryEvent>,
+    mut planet_events: ResMut<Events<PlanetEvent>>,
+    mut map_events: ResMut<Events<MapEvent>>,
+    mut planet_reader: Local<ManualEventReader<PlanetEvent>>,
+    mut map_reader: Local<ManualEventReader<MapEvent>>,
+    mut state: Local<Option<MapState>>,
+    config: Res<MapConfig>,
+    time: Res<Time>,
+    changed: Query<(Entity, &Location, &PlanetData), PlanetChanged>,
+    removed: RemovedComponents<PlanetData>,
+) {
+    let state = state.get_or_insert_with(|| MapState::new(&config));
+    let requests: Vec<_> = requests.iter().collect();
+    if requests.iter().any(|r| matches!(r, HistoryEvent::Clear)) {
+        // the events of the load are not an edit either
+        history.clear();
+        planet_reader.iter(&planet_events).for_each(drop);
+        map_reader.iter(&map_events).for_each(drop);
+    } else {
+        history.record(
+            state,
+            planet_reader.iter(&planet_events),
+            map_reader.iter(&map_events),
+            time.seconds_since_startup(),
+        );
+    }
+
+    for (id, loc, data) in changed.iter() {
+        state.planets.insert(id, (*loc, data.clone()));
+    }
+    for id in removed.iter() {
+        state.planets.remove(&id);
+    }
+    state.set_config(&config);
+
+    for request in requests {
+        let replay = match request {
+            HistoryEvent::Undo => history.undo(state),
+            HistoryEvent::Redo => history.redo(state),
+            HistoryEvent::Clear => continue,
+        };
+        for event in replay {
+            match event {
+                Replay::Planet(event) => planet_events.send(event),
+                Replay::Map(event) => map_events.send(event),
+            }
+        }
+    }
+    // replayed events are not edits of their own
+    planet_reader.iter(&planet_events).for_each(drop);
+    map_reader.iter(&map_events).for_each(drop);
+}
+
+/// Everything an edit can change, as it was at the end of the last frame.
+#[derive(Clone, Debug)]
+pub struct MapState {
+    pub planets: HashMap<Entity, (Location, PlanetData)>,
+    pub ty: MapType,
+    pub params: GridParams,
+    pub symmetry: Symmetry,
+    pub bg_color: Color,
+}
+
+impl MapState {
+    pub fn new(config: &MapConfig) -> Self {
+        Self {
+            planets: HashMap::new(),
+            ty: config.ty,
+            params: config.params,
+            symmetry: config.symmetry,
+            bg_color: config.bg_color,
+        }
+    }
+
+    fn set_config(&mut self, config: &MapConfig) {
+        self.ty = config.ty;
+        self.params = config.params;
+        self.symmetry = config.symmetry;
+        self.bg_color = config.bg_color;
+    }
+
+    /// The planet on `loc`, the one called `name` when several share the cell.
+    fn find(&self, loc: Location, name: &str) -> Option<Entity> {
+        let on_cell: Vec<_> = self
+            .planets
+            .iter()
+            .filter(|(_, (l, _))| *l == loc)
+            .collect();
+        on_cell
+            .iter()
+            .find(|(_, (_, d))| d.name == name)
+            .or_else(|| on_cell.first())
+            .map(|(id, _)| **id)
+    }
+}
+
+/// An event that undoing or redoing sends out.
+#[derive(Clone, Debug)]
+pub enum Replay {
+    Planet(PlanetEvent),
+    Map(MapEvent),
+}
+
+#[derive(Clone, Debug)]
+enum Edit {
+    /// `data` is only known once the planet exists, plain `Create` picks a random name.
+    Create {
+        loc: Location,
+        player: Player,
+        data: Option<PlanetData>,
+    },
+    Delete {
+        loc: Location,
+        data: PlanetData,
+    },
+    SetPlayer {
+        loc: Location,
+        name: String,
+        old: Player,
+        new: Player,
+    },
+    SetName {
+        loc: Location,
+        old: String,
+        new: String,
+    },
+    SetShipCount {
+        loc: Location,
+        name: String,
+        old: usize,
+        new: usize,
+    },
+    SetAttribute {
+        loc: Location,
+        name: String,
+        key: &'static str,
+        old: AttributeValue,
+        new: AttributeValue,
+    },
+    Move {
+        from: Location,
+        to: Location,
+        name: String,
+    },
+    Map {
+        old: MapEvent,
+        new: MapEvent,
+    },
+    /// Planets are put back where they were before the conversion by recreating them.
+    Convert {
+        from: MapType,
+        to: MapType,
+        planets: Vec<(Location, PlanetData)>,
+    },
+}
+
+type Step = Vec<Edit>;
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// When the last step was recorded or merged into.
+    last_edit: f64,
+}
+
+impl History {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Records the events of one frame as a step, `before` is the map before any of them and
+    /// `time` is in seconds.
+    pub fn record<'a>(
+        &mut self,
+        before: &MapState,
+        planet_events: impl IntoIterator<Item = &'a PlanetEvent>,
+        map_events: impl IntoIterator<Item = &'a MapEvent>,
+        time: f64,
+    ) {
+        let mut step = Step::new();
+        for event in map_events {
+            let old = match event {
+                MapEvent::SetColor(_) => MapEvent::SetColor(before.bg_color),
+                MapEvent::SetType(_) => MapEvent::SetType(before.ty),
+                MapEvent::SetParams(_) => MapEvent::SetParams(before.params),
+                MapEvent::SetSymmetry(_) => MapEvent::SetSymmetry(before.symmetry),
+                MapEvent::Convert(to) => {
+                    step.push(Edit::Convert {
+                        from: before.ty,
+                        to: *to,
+                        planets: before.planets.values().cloned().collect(),
+                    });
+                    continue;
+                }
+                MapEvent::Focus(_) => continue,
+            };
+            step.push(Edit::Map {
+                old,
+                new: event.clone(),
+            });
+        }
+
+        for event in planet_events {
+            let planet = |id: &Entity| before.planets.get(id).cloned();
+            let edit = match event {
+                PlanetEvent::Create { loc, player } => Edit::Create {
+                    loc: *loc,
+                    player: *player,
+                    data: None,
+                },
+                PlanetEvent::CreateNamed { loc, data } => Edit::Create {
+                    loc: *loc,
+                    player: data.player,
+                    data: Some(data.clone()),
+                },
+                PlanetEvent::Delete { id } => match planet(id) {
+                    Some((loc, data)) => Edit::Delete { loc, data },
+                    None => continue,
+                },
+                PlanetEvent::SetPlayer { id, player } => match planet(id) {
+                    Some((loc, data)) => Edit::SetPlayer {
+                        loc,
+                        name: data.name,
+                        old: data.player,
+                        new: *player,
+                    },
+                    None => continue,
+                },
+                PlanetEvent::SetName { id, name } => match planet(id) {
+                    Some((loc, data)) => Edit::SetName {
+                        loc,
+                        old: data.name,
+                        new: name.clone(),
+                    },
+                    None => continue,
+                },
+                PlanetEvent::SetShipCount { id, amount } => match planet(id) {
+                    Some((loc, data)) => Edit::SetShipCount {
+                        loc,
+                        name: data.name,
+                        old: data.ship_count,
+                        new: *amount,
+                    },
+                    None => continue,
+                },
+                PlanetEvent::Move { id, to } => match planet(id) {
+                    Some((from, data)) => Edit::Move {
+                        from,
+                        to: *to,
+                        name: data.name,
+                    },
+                    None => continue,
+                },
+                PlanetEvent::SetAttribute { id, key, value } => {
+                    match (planet(id), attribute(key)) {
+                        (Some((loc, data)), Some(attr)) => Edit::SetAttribute {
+                            loc,
+                            key,
+                            old: data.attributes.get(attr),
+                            new: value.clone(),
+                            name: data.name,
+                        },
+                        _ => continue,
+                    }
+                }
+                PlanetEvent::SetSelected { .. } => continue,
+            };
+            step.push(edit);
+        }
+
+        if step.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        let burst = time - self.last_edit <= MERGE_WINDOW;
+        self.last_edit = time;
+        if let Some(top) = self.undo.last_mut() {
+            if burst && merge(top, &step) {
+                return;
+            }
+        }
+        self.undo.push(step);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Events that take back the last step, `now` is the map as it is.
+    pub fn undo(&mut self, now: &MapState) -> Vec<Replay> {
+        let mut step = match self.undo.pop() {
+            Some(step) => step,
+            None => return Vec::new(),
+        };
+        let mut out = Vec::new();
+        for edit in step.iter_mut().rev() {
+            undo_edit(edit, now, &mut out);
+        }
+        self.redo.push(step);
+        // an edit right after does not continue the step before
+        self.last_edit = f64::NEG_INFINITY;
+        out
+    }
+
+    /// Events that do the last undone step again.
+    pub fn redo(&mut self, now: &MapState) -> Vec<Replay> {
+        let step = match self.redo.pop() {
+            Some(step) => step,
+            None => return Vec::new(),
+        };
+        let mut out = Vec::new();
+        for edit in step.iter() {
+            redo_edit(edit, now, &mut out);
+        }
+        self.undo.push(step);
+        self.last_edit = f64::NEG_INFINITY;
+        out
+    }
+}
+
+/// Typing a name or ship count sends an event for every key and dragging a value, grid and
+/// symmetry settings included, one for every frame. Those become one step when they follow each
+/// other within [`MERGE_WINDOW`].
+fn merge(top: &mut Step, step: &Step) -> bool {
+    let same = top.len() == step.len()
+        && top.iter().zip(step).all(|(a, b)| match (a, b) {
+            (Edit::SetName { loc: l1, new, .. }, Edit::SetName { loc: l2, old, .. }) => {
+                l1 == l2 && new == old
+            }
+            (Edit::SetShipCount { loc: l1, .. }, Edit::SetShipCount { loc: l2, .. }) => l1 == l2,
+            (
+                Edit::SetAttribute {
+                    loc: l1, key: k1, ..
+                },
+                Edit::SetAttribute {
+                    loc: l2, key: k2, ..
+                },
+            ) => l1 == l2 && k1 == k2,
+            (Edit::Map { new: a, .. }, Edit::Map { new: b, .. }) => matches!(
+                (a, b),
+                (MapEvent::SetParams(_), MapEvent::SetParams(_))
+                    | (MapEvent::SetSymmetry(_), MapEvent::SetSymmetry(_))
+            ),
+            _ => false,
+        });
+    if !same {
+        return false;
+    }
+    for (a, b) in top.iter_mut().zip(step) {
+        match (a, b) {
+            (Edit::SetName { new, .. }, Edit::SetName { new: next, .. }) => *new = next.clone(),
+            (Edit::SetShipCount { new, .. }, Edit::SetShipCount { new: next, .. }) => *new = *next,
+            (Edit::SetAttribute { new, .. }, Edit::SetAttribute { new: next, .. }) => {
+                *new = next.clone()
+            }
+            (Edit::Map { new, .. }, Edit::Map { new: next, .. }) => *new = next.clone(),
+            _ => unreachable!(),
+        }
+    }
+    true
+}
+
+fn undo_edit(edit: &mut Edit, now: &MapState, out: &mut Vec<Replay>) {
+    match edit {
+        Edit::Create { loc, data, .. } => {
+            let name = data.as_ref().map_or("", |d| d.name.as_str());
+            if let Some(id) = now.find(*loc, name) {
+                *data = Some(now.planets[&id].1.clone());
+                out.push(Replay::Planet(PlanetEvent::Delete { id }));
+            }
+        }
+        Edit::Delete { loc, data } => out.push(Replay::Planet(PlanetEvent::CreateNamed {
+            loc: *loc,
+            data: data.clone(),
+        })),
+        Edit::SetPlayer { loc, name, old, .. } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetPlayer { id, player: *old }));
+            }
+        }
+        Edit::SetName { loc, old, new } => {
+            if let Some(id) = now.find(*loc, new) {
+                out.push(Replay::Planet(PlanetEvent::SetName {
+                    id,
+                    name: old.clone(),
+                }));
+            }
+        }
+        Edit::SetShipCount { loc, name, old, .. } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetShipCount {
+                    id,
+                    amount: *old,
+                }));
+            }
+        }
+        Edit::SetAttribute {
+            loc,
+            name,
+            key,
+            old,
+            ..
+        } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetAttribute {
+                    id,
+                    key,
+                    value: old.clone(),
+                }));
+            }
+        }
+        Edit::Move { from, to, name } => {
+            if let Some(id) = now.find(*to, name) {
+                out.push(Replay::Planet(PlanetEvent::Move { id, to: *from }));
+            }
+        }
+        Edit::Map { old, .. } => out.push(Replay::Map(old.clone())),
+        Edit::Convert { from, planets, .. } => {
+            out.push(Replay::Map(MapEvent::SetType(*from)));
+            out.extend(
+                now.planets
+                    .keys()
+                    .map(|id| Replay::Planet(PlanetEvent::Delete { id: *id })),
+            );
+            out.extend(planets.iter().map(|(loc, data)| {
+                Replay::Planet(PlanetEvent::CreateNamed {
+                    loc: *loc,
+                    data: data.clone(),
+                })
+            }));
+        }
+    }
+}
+
+fn redo_edit(edit: &Edit, now: &MapState, out: &mut Vec<Replay>) {
+    match edit {
+        Edit::Create { loc, player, data } => out.push(Replay::Planet(match data {
+            Some(data) => PlanetEvent::CreateNamed {
+                loc: *loc,
+                data: data.clone(),
+            },
+            None => PlanetEvent::Create {
+                loc: *loc,
+                player: *player,
+            },
+        })),
+        Edit::Delete { loc, data } => {
+            if let Some(id) = now.find(*loc, &data.name) {
+                out.push(Replay::Planet(PlanetEvent::Delete { id }));
+            }
+        }
+        Edit::SetPlayer { loc, name, new, .. } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetPlayer { id, player: *new }));
+            }
+        }
+        Edit::SetName { loc, old, new } => {
+            if let Some(id) = now.find(*loc, old) {
+                out.push(Replay::Planet(PlanetEvent::SetName {
+                    id,
+                    name: new.clone(),
+                }));
+            }
+        }
+        Edit::SetShipCount { loc, name, new, .. } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetShipCount {
+                    id,
+                    amount: *new,
+                }));
+            }
+        }
+        Edit::SetAttribute {
+            loc,
+            name,
+            key,
+            new,
+            ..
+        } => {
+            if let Some(id) = now.find(*loc, name) {
+                out.push(Replay::Planet(PlanetEvent::SetAttribute {
+                    id,
+                    key,
+                    value: new.clone(),
+                }));
+            }
+        }
+        Edit::Move { from, to, name } => {
+            if let Some(id) = now.find(*from, name) {
+                out.push(Replay::Planet(PlanetEvent::Move { id, to: *to }));
+            }
+        }
+        Edit::Map { new, .. } => out.push(Replay::Map(new.clone())),
+        Edit::Convert { to, .. } => out.push(Replay::Map(MapEvent::Convert(*to))),
+    }
+}
@@ -0,0 +1,242 @@
+//! Reading maps that were exported for planetwars back into the editor.
+//!
+//! The export only has world positions, scaled so the longest expedition takes the chosen number
+//! of turns. On import they are scaled again so the two closest planets end up `spacing` cells
+//! apart, and every planet goes to the nearest free cell of the chosen [`MapType`].
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    attributes::{attribute, AttributeKind, AttributeValue, Attributes},
+    geometry::{convert, GridParams, Point},
+    map_config::MapType,
+    migration::VERSION,
+    planet::{PlanetData, Player, Players},
+    scene::{Scene, SceneConfig, ScenePlanet},
+};
+
+#[derive(Deserialize)]
+struct ExportedPlanet {
+    name: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    owner: Option<usize>,
+    #[serde(default)]
+    ship_count: usize,
+    /// Planet attributes, when they were exported.
+    #[serde(flatten)]
+    rest: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ExportedPlayer {
+    id: usize,
+    name: String,
+    #[serde(default)]
+    team: usize,
+}
+
+#[derive(Deserialize)]
+struct ExportedMap {
+    planets: Vec<ExportedPlanet>,
+    #[serde(default)]
+    name: String,
+    /// Only the JSON with metadata has players.
+    #[serde(default)]
+    players: Vec<ExportedPlayer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// Not JSON, or not shaped like an export.
+    Json(String),
+    /// The export has no planets.
+    Empty,
+    /// No cell of the map type is close to the planet.
+    NoCell(String),
+    /// An owner or player id above [`Players::MAX`].
+    TooManyPlayers(usize),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Json(e) => write!(f, "Not a planetwars map: {}", e),
+            ImportError::Empty => write!(f, "The map has no planets"),
+            ImportError::NoCell(name) => write!(f, "No cell close to planet {}", name),
+            ImportError::TooManyPlayers(n) => write!(
+                f,
+                "The map has player {}, at most {} players are supported",
+                n,
+                Players::MAX
+            ),
+        }
+    }
+}
+
+/// How an import went, for the editor to show.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportReport {
+    pub planets: usize,
+    /// Planets that did not get the cell under them, because another planet was closer to it.
+    pub moved: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub name: String,
+    pub scene: Scene,
+    pub report: ImportReport,
+}
+
+/// Reads the export `text` onto a grid of type `ty` with `params`, so the two closest planets
+/// are `spacing` cells apart.
+pub fn import(
+    text: &str,
+    ty: MapType,
+    params: GridParams,
+    spacing: f32,
+) -> Result<Import, ImportError> {
+    let map: ExportedMap =
+        serde_json::from_str(text).map_err(|e| ImportError::Json(e.to_string()))?;
+    if map.planets.is_empty() {
+        return Err(ImportError::Empty);
+    }
+    let players = players(&map)?;
+
+    let n = map.planets.len() as f32;
+    let mid = map.planets.iter().fold(Point::default(), |m, p| {
+        Point::new(m.x + p.x / n, m.y + p.y / n)
+    });
+    let points: Vec<_> = map
+        .planets
+        .iter()
+        .map(|p| Point::new(p.x - mid.x, p.y - mid.y))
+        .collect();
+
+    let (cell, _) = params.transform(ty);
+    let closest = closest_pair(&points);
+    let scale = if closest > 0. {
+        spacing * cell.x.max(cell.y) / closest
+    } else {
+        1.
+    };
+    let points: Vec<_> = points
+        .iter()
+        .map(|p| Point::new(p.x * scale, p.y * scale))
+        .collect();
+
+    let config = SceneConfig {
+        ty,
+        params,
+        symmetry: Default::default(),
+    };
+    let placements = convert::place(config.grid().as_ref(), &points);
+
+    let mut moved = Vec::new();
+    let mut planets = Vec::new();
+    for (planet, placement) in map.planets.iter().zip(placements) {
+        let placement = placement.ok_or_else(|| ImportError::NoCell(planet.name.clone()))?;
+        if placement.taken.is_some() {
+            moved.push(planet.name.clone());
+        }
+        planets.push(ScenePlanet {
+            data: PlanetData {
+                name: planet.name.clone(),
+                player: Player(planet.owner.unwrap_or(0)),
+                ship_count: planet.ship_count,
+                attributes: attributes(&planet.rest),
+            },
+            location: placement.to,
+        });
+    }
+
+    Ok(Import {
+        name: map.name,
+        report: ImportReport {
+            planets: planets.len(),
+            moved,
+        },
+        scene: Scene {
+            version: VERSION,
+            config,
+            planets,
+            players: Some(players),
+        },
+    })
+}
+
+/// Distance between the two planets that are closest together, 0 for a single planet.
+fn closest_pair(points: &[Point]) -> f32 {
+    let mut closest = f32::MAX;
+    for (i, p1) in points.iter().enumerate() {
+        for p2 in &points[i + 1..] {
+            closest = closest.min(p1.dist(*p2));
+        }
+    }
+    if closest == f32::MAX {
+        0.
+    } else {
+        closest
+    }
+}
+
+/// The default players, with as many extra ones as the owners need and the names and teams of
+/// the export.
+fn players(map: &ExportedMap) -> Result<Players, ImportError> {
+    let highest = map
+        .planets
+        .iter()
+        .filter_map(|p| p.owner)
+        .chain(map.players.iter().map(|p| p.id))
+        .max()
+        .unwrap_or(0);
+    if highest > Players::MAX {
+        return Err(ImportError::TooManyPlayers(highest));
+    }
+
+    let mut players = Players::default();
+    while players.count() < highest {
+        players.add();
+    }
+    for exported in map.players.iter().filter(|p| p.id != 0) {
+        if let Some(info) = players.players.get_mut(exported.id) {
+            info.name = exported.name.clone();
+            info.team = exported.team;
+        }
+    }
+    Ok(players)
+}
+
+/// The attributes of the registry in `values`, everything else is dropped.
+fn attributes(values: &Map<String, Value>) -> Attributes {
+    let mut attributes = Attributes::default();
+    for (key, value) in values {
+        let attr = match attribute(key) {
+            Some(attr) => attr,
+            None => continue,
+        };
+        let value = match (attr.kind, value) {
+            (AttributeKind::Int { .. }, Value::Number(n)) => n.as_i64().map(AttributeValue::Int),
+            (AttributeKind::Float { .. }, Value::Number(n)) => {
+                n.as_f64().map(AttributeValue::Float)
+            }
+            (AttributeKind::Text, Value::String(s)) => Some(AttributeValue::Text(s.clone())),
+            (AttributeKind::Tags, Value::Array(tags)) => Some(AttributeValue::Text(
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
+            _ => None,
+        };
+        if let Some(value) = value.filter(|v| attr.accepts(v)) {
+            attributes.set(attr, value);
+        }
+    }
+    attributes
+}
@@ -5,8 +5,11 @@ use bevy::{
 };
 
 use crate::{
+    clipboard::Clipboard,
+    geometry::Point,
     map_config::{MapConfig, MapEvent, MapType},
     planet::{HoverPlanet, Location, PlanetEvent, Player, Selected},
+    selection::{plan_move, within, MovePreview, SelectionBox},
     HoveringUI,
 };
 
@@ -125,10 +128,13 @@ pub fn world_move(
 
 pub fn change_bg_color(
     input: Res<Input<KeyCode>>,
+    config: Res<MapConfig>,
     hovering_ui: Res<HoveringUI>,
     mut writer: EventWriter<MapEvent>,
 ) {
-    if hovering_ui.0 {
+    // leave ctrl + z to undo
+    let ctrl = input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if hovering_ui.0 || ctrl {
         return;
     }
 
@@ -141,52 +147,177 @@ pub fn change_bg_color(
     }
 
     if input.just_pressed(KeyCode::Z) {
-        writer.send(MapEvent::SetType(MapType::Triangles));
+        writer.send(config.switch_to(MapType::Triangles));
     }
 
     if input.just_pressed(KeyCode::X) {
-        writer.send(MapEvent::SetType(MapType::Squares));
+        writer.send(config.switch_to(MapType::Squares));
     }
 
     if input.just_pressed(KeyCode::C) {
-        writer.send(MapEvent::SetType(MapType::Hexagons));
+        writer.send(config.switch_to(MapType::Hexagons));
     }
 }
 
+/// Pixels the mouse moves with the button down before a click becomes a drag.
+const DRAG_DISTANCE: f32 = 4.;
+
+/// Where the left mouse button went down.
+#[derive(Clone, Copy)]
+pub struct Press {
+    at: Point,
+    /// The planet under the mouse, dragging it moves it.
+    planet: Option<(Entity, Location)>,
+}
+
+/// Left clicks create or select planets, dragging from an empty cell selects everything in a
+/// box. Shift adds to the selection and ctrl removes from it. Dragging a planet moves it, or
+/// the whole selection when it is selected.
 #[allow(clippy::too_many_arguments)]
 pub fn spawn_planet(
     click: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
     location: Query<&Location, With<HoverPlanet>>,
     planets: Query<(Entity, &Location, &Selected), Without<HoverPlanet>>,
     hovering_ui: Res<HoveringUI>,
     mut planet_events: EventWriter<PlanetEvent>,
     current_player: Res<Player>,
+    config: Res<MapConfig>,
+    mut selection: ResMut<SelectionBox>,
+    mut preview: ResMut<MovePreview>,
+    clipboard: Res<Clipboard>,
+    mut press: Local<Option<Press>>,
 ) {
-    if hovering_ui.0 {
+    let loc = location.single();
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let planet_at = |loc: &Location| planets.iter().find(|(_, l, _)| *l == loc);
+    let mut selected = Vec::new();
+    let mut moving = Vec::new();
+
+    // the box keeps following the mouse over the side panel
+    if let (Some(p), Some(mouse)) = (*press, config.mouse_world()) {
+        let dragged = p.at.dist(mouse) * config.zoom > DRAG_DISTANCE;
+        match p.planet {
+            None if selection.start.is_some() || dragged => {
+                selection.start = Some(p.at);
+                selection.end = mouse;
+            }
+            Some((grabbed, from)) if !preview.targets.is_empty() || dragged => {
+                let with_selection = planets.iter().any(|(e, _, s)| e == grabbed && s.0);
+                let (mut still, mut moved) = (Vec::new(), Vec::new());
+                for (e, l, s) in planets.iter() {
+                    if e == grabbed || (with_selection && s.0) {
+                        moving.push(e);
+                        moved.push(*l);
+                    } else {
+                        still.push(*l);
+                    }
+                }
+                let plan = plan_move(config.grid().as_ref(), from, *loc, &moved, &still);
+                if *preview != plan {
+                    *preview = plan;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if click.just_released(MouseButton::Left) && press.take().is_some() {
+        if !preview.targets.is_empty() {
+            let plan = std::mem::take(&mut *preview);
+            if !plan.blocked {
+                planet_events.send_batch(
+                    moving
+                        .into_iter()
+                        .zip(plan.targets)
+                        .map(|(id, to)| PlanetEvent::Move { id, to }),
+                );
+            }
+        } else if let Some(start) = selection.start.take() {
+            let grid = config.grid();
+            for (e, l, s) in planets.iter() {
+                let inside = within(start, selection.end, grid.center(*l));
+                let keep = match (shift, ctrl) {
+                    (true, _) => s.0 || inside,
+                    (_, true) => s.0 && !inside,
+                    _ => inside,
+                };
+                selected.push((e, s, keep));
+            }
+        } else if let Some((e, _, s)) = planet_at(loc) {
+            if shift || ctrl {
+                selected.push((e, s, shift));
+            } else {
+                // a lone selected planet is deselected, like toggling it
+                let others = planets.iter().any(|(o, _, os)| o != e && os.0);
+                selected.extend(
+                    planets
+                        .iter()
+                        .filter(|(o, _, _)| *o != e)
+                        .map(|(o, _, os)| (o, os, false)),
+                );
+                selected.push((e, s, others || !s.0));
+            }
+        } else if !(shift || ctrl || hovering_ui.0) {
+            create_planet(*loc, &planets, &mut planet_events, &current_player, &config);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        selected.extend(planets.iter().map(|(e, _, s)| (e, s, false)));
+    }
+
+    planet_events.send_batch(
+        selected
+            .into_iter()
+            .filter(|(_, s, keep)| s.0 != *keep)
+            .map(|(id, _, selected)| PlanetEvent::SetSelected { id, selected }),
+    );
+
+    // clicks place or drop a paste
+    if hovering_ui.0 || clipboard.pending.is_some() {
         return;
     }
 
-    let loc = location.single();
     if click.just_pressed(MouseButton::Left) {
-        if let Some((e, _, s)) = planets.iter().find(|(_, l, _)| *l == loc) {
-            planet_events.send(PlanetEvent::SetSelected {
-                id: e,
-                selected: !s.0,
-            });
-        } else {
-            planet_events.send(PlanetEvent::Create {
-                loc: *location.single(),
-                player: *current_player,
-            });
-        }
+        *press = config.mouse_world().map(|at| Press {
+            at,
+            planet: planet_at(loc).map(|(e, l, _)| (e, *l)),
+        });
     }
 
     if click.just_pressed(MouseButton::Right) {
+        let counterparts = config.symmetry.counterparts(config.grid().as_ref(), *loc);
         planet_events.send_batch(
             planets
                 .iter()
-                .filter(|(_, l, _)| *l == loc)
+                .filter(|(_, l, _)| *l == loc || counterparts.iter().any(|(c, _)| c == *l))
                 .map(|(e, _, _)| PlanetEvent::Delete { id: e }),
         );
     }
 }
+
+/// Creates a planet for the current player on `loc`, and on its symmetric counterparts.
+fn create_planet(
+    loc: Location,
+    planets: &Query<(Entity, &Location, &Selected), Without<HoverPlanet>>,
+    planet_events: &mut EventWriter<PlanetEvent>,
+    current_player: &Player,
+    config: &MapConfig,
+) {
+    planet_events.send(PlanetEvent::Create {
+        loc,
+        player: *current_player,
+    });
+
+    // counterparts that are already taken keep their planet
+    let taken = |other| planets.iter().any(|(_, l, _)| *l == other);
+    let counterparts = config.symmetry.counterparts(config.grid().as_ref(), loc);
+    planet_events.send_batch(counterparts.iter().filter(|(other, _)| !taken(*other)).map(
+        |(other, k)| PlanetEvent::Create {
+            loc: *other,
+            player: Player(config.symmetry.permute(current_player.0, *k)),
+        },
+    ));
+}
@@ -5,13 +5,25 @@ use bevy::{
 use egui::Color32;
 use petname::Petnames;
 
+pub mod analysis;
+pub mod attributes;
 pub mod background;
+pub mod clipboard;
+pub mod export;
+pub mod generator;
+pub mod geometry;
+pub mod history;
+pub mod import;
 pub mod input;
 pub mod map_config;
+pub mod migration;
 pub mod planet;
 pub mod scene;
+pub mod selection;
 pub mod ui;
+pub mod upload;
 pub mod utils;
+pub mod validation;
 
 pub struct HoveringUI(pub bool);
 
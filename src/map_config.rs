@@ -1,17 +1,46 @@
 use bevy::{
-    math::{Vec2, Vec3},
+    math::Vec3,
     prelude::*,
     render::mesh::{Indices, PrimitiveTopology},
     sprite::Mesh2dHandle,
 };
 
-use crate::planet::{HoverPlanet, Location, PlanetEntity, PlanetMesh, PlanetName};
-
-use serde::{Deserialize, Serialize};
+pub use crate::geometry::MapType;
+use crate::geometry::{convert, symmetry::Symmetry, Grid, GridParams, Point};
+use crate::planet::{
+    self, HoverPlanet, Location, PlanetData, PlanetEntity, PlanetMesh, PlanetName, PlanetOutline,
+};
 
+#[derive(Clone, Debug)]
 pub enum MapEvent {
     SetColor(Color),
+    /// Switches the grid, planets keep their `Location` indices.
     SetType(MapType),
+    /// Switches the grid, planets move to the nearest free cell of the new grid.
+    Convert(MapType),
+    /// Changes the shape of the grid, planets keep their `Location` indices.
+    SetParams(GridParams),
+    SetSymmetry(Symmetry),
+    /// Moves the view so the cell is in the middle of the screen.
+    Focus(Location),
+}
+
+/// A planet that could not go to the cell under it after a [`MapEvent::Convert`].
+#[derive(Clone, Debug)]
+pub struct Collision {
+    pub planet: Entity,
+    pub name: String,
+    /// The cell under the planet, already taken by another planet.
+    pub wanted: Location,
+    /// Where the planet went instead, `wanted` when there was no free cell close by.
+    pub placed: Location,
+}
+
+/// Outcome of the last [`MapEvent::Convert`], shown in the side panel until dismissed.
+#[derive(Default)]
+pub struct ConversionReport {
+    pub ty: Option<MapType>,
+    pub collisions: Vec<Collision>,
 }
 
 pub struct MapConfigPlugin;
@@ -19,7 +48,9 @@ impl Plugin for MapConfigPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_startup_system_to_stage(StartupStage::PreStartup, setup_config)
             .add_event::<MapEvent>()
-            .add_system(handle_map_events);
+            .init_resource::<ConversionReport>()
+            // planets created in the same frame get the meshes of the new map type
+            .add_system(handle_map_events.before(planet::handle_planet_events));
     }
 }
 
@@ -37,18 +68,41 @@ fn setup_config(
     commands.insert_resource(config);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_map_events(
     mut reader: EventReader<MapEvent>,
     mut config: ResMut<MapConfig>,
+    mut mesh_assets: ResMut<Assets<Mesh>>,
+    mut report: ResMut<ConversionReport>,
 
-    mut hover_planet: Query<(&mut Transform, &mut Mesh2dHandle, &Location), With<HoverPlanet>>,
+    mut hover_planet: Query<(&mut Transform, &mut Mesh2dHandle, &mut Location), With<HoverPlanet>>,
 
-    mut locations: Query<(&PlanetEntity, &Location), (Without<HoverPlanet>, Without<PlanetMesh>)>,
+    mut locations: Query<
+        (Entity, &PlanetEntity, &mut Location, &PlanetData),
+        (Without<HoverPlanet>, Without<PlanetMesh>),
+    >,
     mut meshes: Query<
         (&mut Transform, &mut Mesh2dHandle),
         (Without<HoverPlanet>, With<PlanetMesh>, Without<PlanetName>),
     >,
     mut names: Query<&mut Transform, (Without<HoverPlanet>, With<PlanetName>, Without<PlanetMesh>)>,
+    mut outlines: Query<
+        &mut Mesh2dHandle,
+        (
+            With<PlanetOutline>,
+            Without<PlanetMesh>,
+            Without<HoverPlanet>,
+        ),
+    >,
+    mut cameras: Query<
+        &mut Transform,
+        (
+            With<Camera2d>,
+            Without<HoverPlanet>,
+            Without<PlanetMesh>,
+            Without<PlanetName>,
+        ),
+    >,
 ) {
     let mut update_meshes = false;
     for event in reader.iter() {
@@ -57,45 +111,97 @@ fn handle_map_events(
                 config.ty = *ty;
                 update_meshes = true;
             }
+            MapEvent::Convert(ty) => {
+                let from = config.grid();
+                config.ty = *ty;
+                let to = config.grid();
+
+                let planets: Vec<_> = locations.iter().map(|(_, _, loc, _)| *loc).collect();
+                let placements = convert::relocate(from.as_ref(), to.as_ref(), &planets);
+
+                report.ty = Some(*ty);
+                report.collisions.clear();
+                for ((e, _, mut loc, data), placement) in locations.iter_mut().zip(placements) {
+                    *loc = placement.to;
+                    if let Some(wanted) = placement.taken {
+                        report.collisions.push(Collision {
+                            planet: e,
+                            name: data.name.clone(),
+                            wanted,
+                            placed: placement.to,
+                        });
+                    }
+                }
+                update_meshes = true;
+            }
             MapEvent::SetColor(color) => {
                 config.bg_color = *color;
             }
+            MapEvent::SetSymmetry(symmetry) => {
+                config.symmetry = *symmetry;
+            }
+            MapEvent::Focus(loc) => {
+                let center = config.grid().center(*loc);
+                config.x = -center.x * config.zoom;
+                config.y = -center.y * config.zoom;
+                for mut cam_trans in cameras.iter_mut() {
+                    cam_trans.translation.x = center.x;
+                    cam_trans.translation.y = center.y;
+                }
+                if let Some(l) = config.recalculate() {
+                    for (_, _, mut loc) in hover_planet.iter_mut() {
+                        *loc = l;
+                    }
+                }
+            }
+            MapEvent::SetParams(params) => {
+                config.params = *params;
+                for (ty, meshes) in config.meshes.iter_mut() {
+                    *meshes =
+                        MapConfig::mesh_asset(ty.grid_with(params).as_ref(), &mut mesh_assets);
+                }
+                if let Some(l) = config.recalculate() {
+                    for (_, _, mut loc) in hover_planet.iter_mut() {
+                        *loc = l;
+                    }
+                }
+                update_meshes = true;
+            }
         }
     }
 
     if update_meshes {
-        for (e, loc) in locations.iter_mut() {
+        for (_, e, loc, _) in locations.iter() {
             let (mut t, mut l) = meshes.get_mut(e.mesh).unwrap();
 
             *t = config.shape_transform(loc, 0.5);
             *l = config.mesh(loc).into();
+            if let Ok(mut l) = outlines.get_mut(e.outline) {
+                *l = config.mesh(loc).into();
+            }
 
             let mut t = names.get_mut(e.name).unwrap();
             *t = config.text_transform(loc);
         }
 
         for (mut t, mut l, loc) in hover_planet.iter_mut() {
-            *l = config.mesh(loc).into();
+            *l = config.mesh(&loc).into();
 
             *t = config
-                .shape_transform(loc, 0.1)
-                .mul_transform(config.text_transform(loc));
+                .shape_transform(&loc, 0.1)
+                .mul_transform(config.text_transform(&loc));
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-pub enum MapType {
-    Squares,
-    Triangles,
-    Hexagons,
-    Octagons,
-}
-
-const TRIAG_HEIGHT: f32 = 0.866_025_4; // sqrt(1 - 0.25) height of equal triangle
-
 pub struct MapConfig {
     pub ty: MapType,
+    pub params: GridParams,
+    /// Placing, deleting or editing a planet does the same to its counterparts.
+    pub symmetry: Symmetry,
+    /// Whether switching the map type from the editor sends [`MapEvent::Convert`] or
+    /// [`MapEvent::SetType`].
+    pub keep_positions: bool,
 
     pub zoom: f32,
 
@@ -112,11 +218,9 @@ pub struct MapConfig {
 
     pub font: Handle<Font>,
 
-    meshes: Vec<(MapType, LocPred, Handle<Mesh>)>,
+    meshes: Vec<(MapType, Vec<Handle<Mesh>>)>,
 }
 
-type LocPred = Box<dyn Fn(&Location) -> bool + Send + Sync + 'static>;
-
 impl MapConfig {
     pub fn new(
         width: f32,
@@ -124,22 +228,16 @@ impl MapConfig {
         asset_server: &AssetServer,
         mesh_assets: &mut Assets<Mesh>,
     ) -> Self {
-        let meshes = [
-            MapType::Squares,
-            MapType::Triangles,
-            MapType::Hexagons,
-            MapType::Octagons,
-        ]
-        .into_iter()
-        .map(|x| (x, MapConfig::mesh_asset(x, mesh_assets)))
-        .flat_map(|(x, ff)| {
-            let x = x;
-            ff.into_iter().map(move |(y, z)| (x, y, z))
-        })
-        .collect();
+        let meshes = MapType::ALL
+            .into_iter()
+            .map(|x| (x, MapConfig::mesh_asset(x.grid().as_ref(), mesh_assets)))
+            .collect();
         let font = asset_server.load("fonts/FiraSans-Bold.ttf");
         Self {
             ty: MapType::Triangles,
+            params: GridParams::default(),
+            symmetry: Symmetry::default(),
+            keep_positions: true,
             zoom: 100.,
             width,
             height,
@@ -155,72 +253,33 @@ impl MapConfig {
         }
     }
 
+    /// The event that switches to `ty` the way the editor is set up to.
+    pub fn switch_to(&self, ty: MapType) -> MapEvent {
+        if self.keep_positions {
+            MapEvent::Convert(ty)
+        } else {
+            MapEvent::SetType(ty)
+        }
+    }
+
+    pub fn grid(&self) -> Box<dyn Grid + Send + Sync> {
+        self.ty.grid_with(&self.params)
+    }
+
     pub fn mesh(&self, loc: &Location) -> Handle<Mesh> {
+        let variant = self.grid().variant(*loc);
         self.meshes
             .iter()
-            .find(|x| x.0 == self.ty && (x.1)(loc))
-            .map(|x| x.2.clone_weak())
+            .find(|x| x.0 == self.ty)
+            .map(|x| x.1[variant].clone_weak())
             .unwrap()
     }
 
-    fn mesh_asset(ty: MapType, mesh_assets: &mut Assets<Mesh>) -> Vec<(LocPred, Handle<Mesh>)> {
-        match ty {
-            MapType::Octagons => {
-                vec![
-                    (
-                        Box::new(|loc| loc.y % 2 == 0),
-                        mesh_assets.add(Mesh::from(shape::RegularPolygon {
-                            radius: 0.541,
-                            sides: 8,
-                        })),
-                    ),
-                    (
-                        Box::new(|_| true),
-                        mesh_assets.add(Mesh::from(shape::RegularPolygon {
-                            radius: 0.292,
-                            sides: 4,
-                        })),
-                    ),
-                ]
-            }
-            MapType::Hexagons => vec![(
-                Box::new(|_| true),
-                mesh_assets.add(Mesh::from(shape::RegularPolygon {
-                    radius: 1.0,
-                    sides: 6,
-                })),
-            )],
-            MapType::Squares => vec![(
-                Box::new(|_| true),
-                mesh_assets.add(Mesh::from(shape::Quad::new(Vec2::new(1., 1.)))),
-            )],
-            MapType::Triangles => {
-                let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
-                let th2 = TRIAG_HEIGHT * 0.5;
-                mesh.insert_attribute(
-                    Mesh::ATTRIBUTE_POSITION,
-                    // vec![
-                    //     [-0.5, -TRIAG_HEIGHT * 0.5, 0.0],
-                    //     [0.5, -TRIAG_HEIGHT * 0.5, 0.0],
-                    //     [0., TRIAG_HEIGHT * 0.5, 0.0],
-                    // ],
-                    vec![[-0.5, -th2, 0.0], [0.5, -th2, 0.0], [0., th2, 0.0]],
-                    // vec![[-0.5, 0., 0.0], [0.5, 0., 0.0], [0., TRIAG_HEIGHT, 0.0]],
-                );
-                mesh.insert_attribute(
-                    Mesh::ATTRIBUTE_NORMAL,
-                    vec![[0., 0., 1.], [0., 0., 1.], [0., 0., 1.]],
-                );
-                mesh.insert_attribute(
-                    Mesh::ATTRIBUTE_UV_0,
-                    vec![[0.0, 0.0], [1.0, 0.0], [0.5, TRIAG_HEIGHT]],
-                );
-                mesh.set_indices(Some(Indices::U32(vec![0, 1, 2])));
-
-                let handle = mesh_assets.add(mesh);
-                vec![(Box::new(|_| true), handle)]
-            }
-        }
+    fn mesh_asset(grid: &dyn Grid, mesh_assets: &mut Assets<Mesh>) -> Vec<Handle<Mesh>> {
+        grid.prototypes()
+            .into_iter()
+            .map(|loc| mesh_assets.add(polygon_mesh(&grid.polygon(loc), grid.center(loc))))
+            .collect()
     }
 
     pub fn set_zoom(&mut self, zoom: f32) {
@@ -233,11 +292,16 @@ impl MapConfig {
         self.y = y * self.zoom;
     }
 
-    pub fn recalculate(&self) -> Option<Location> {
-        self.contains(
+    /// World position under the mouse.
+    pub fn mouse_world(&self) -> Option<Point> {
+        Some(Point::new(
             (self.mouse_x? - self.x) / self.zoom,
             (self.mouse_y? - self.y) / self.zoom,
-        )
+        ))
+    }
+
+    pub fn recalculate(&self) -> Option<Location> {
+        self.grid().cell_at(self.mouse_world()?)
     }
 
     pub fn update_mouse(&mut self, x: f32, y: f32) -> Option<Location> {
@@ -247,227 +311,57 @@ impl MapConfig {
         self.recalculate()
     }
 
-    fn contains(&self, x: f32, y: f32) -> Option<Location> {
-        match self.ty {
-            MapType::Octagons => {
-                let x_scaled = x - 0.5;
-                let y_scaled = y - 0.5;
-                let dx = x_scaled.signum() * 0.5;
-                let dy = y_scaled.signum() * 0.5;
-
-                let mut base_x = (dx + x_scaled) as i32;
-                let mut base_y = (dy + y_scaled) as i32 * 2;
-
-                let cx = base_x as f32;
-                let cy = base_y as f32 * 0.5;
-
-                let dx = x_scaled - cx;
-                let dy = y_scaled - cy;
-
-                let ty = 0.586 - dx.abs();
-
-                if dy.abs() > ty {
-                    if dy > 0. {
-                        base_y += 1;
-                    } else {
-                        base_y -= 1;
-                    }
-
-                    if dx < 0. {
-                        base_x -= 1;
-                    }
-                }
-                let loc = Location {
-                    x: base_x,
-                    y: base_y,
-                };
-
-                Some(loc)
-            }
-            MapType::Hexagons => {
-                let dx = x.signum() * 0.5;
-                let dy = y.signum() * 0.5;
-
-                let x_scaled = x / 3.;
-                let y_scaled = y / (2. * 0.866);
-
-                let mut base_x = (dx + x_scaled) as i32;
-                let mut base_y = (dy + y_scaled) as i32 * 2;
-
-                let cx = base_x as f32;
-                let cy = (base_y / 2) as f32;
-
-                let dx = x_scaled - cx;
-                let dy = y_scaled - cy;
-
-                let dx_unscaled = dx * 3.0; // translate to start slope
-                let dy_unscaled = dy;
-
-                let ty = 1. - dx_unscaled.abs();
-
-                if dy_unscaled.abs() > ty {
-                    if dy > 0. {
-                        base_y += 1;
-                    } else {
-                        base_y -= 1;
-                    }
-
-                    if dx < 0. {
-                        base_x -= 1;
-                    }
-                }
-
-                let loc = Location {
-                    x: base_x,
-                    y: base_y,
-                };
-
-                Some(loc)
-            }
-            MapType::Squares => {
-                let dx = x.signum() * 0.5;
-                let dy = y.signum() * 0.5;
-                let out = Location {
-                    x: (x + dx) as i32,
-                    y: (y + dy) as i32,
-                };
-                Some(out)
-            }
-            MapType::Triangles => {
-                let mut x = x * 2.0 + 1.;
-                let y = y + TRIAG_HEIGHT * 0.5;
-                let p = 1.154_700_5; // tan(pi / 6) * 2    //  30 degrees
-                let row = (y / TRIAG_HEIGHT).floor();
-                let mut frac = y - row * TRIAG_HEIGHT;
-                if frac < 0. {
-                    frac += 1.0;
-                }
-
-                if row as i32 % 2 == 0 {
-                    x += 1.0;
-                }
-
-                let mut col_frac = x.fract();
-                if col_frac < 0. {
-                    col_frac += 1.0;
-                }
-                let mut col = x.floor() as i32;
-                let mut triangle_bot_length = 1. - p * frac;
-                if col % 2 == 0 {
-                    triangle_bot_length = 1.0 - triangle_bot_length;
-                }
-
-                if col_frac < triangle_bot_length {
-                    col -= 1;
-                }
-
-                Some(Location {
-                    x: col as i32,
-                    y: row as i32,
-                })
-            }
-        }
-    }
-
     pub fn text_transform(&self, location: &Location) -> Transform {
-        match self.ty {
-            MapType::Octagons => {
-                let mut x = location.x as f32 + 0.5;
-                let y = location.y as f32 * 0.5 + 0.5;
-
-                if (location.y % 2).abs() == 1 {
-                    x += 0.5;
-                }
-
-                let diff = if (location.y % 2).abs() == 1 {
-                    0.35
-                } else {
-                    0.6
-                };
-
-                Transform::default()
-                    .with_translation(Vec3::new(x, y - diff, 1.5))
-                    .with_scale(Vec3::splat(0.01))
-            }
-            MapType::Hexagons => {
-                let mut x = location.x as f32 * 3.0;
-                let y = location.y as f32 * 0.866;
-
-                if (location.y % 2).abs() == 1 {
-                    x += 1.5;
-                }
-
-                Transform::default()
-                    .with_translation(Vec3::new(x, y - 1.1, 1.5))
-                    .with_scale(Vec3::splat(0.01))
-            }
-            MapType::Squares => Transform::default()
-                .with_translation(Vec3::new(location.x as f32, location.y as f32 - 0.6, 1.5))
-                .with_scale(Vec3::splat(0.01)),
-            MapType::Triangles => {
-                let mut mat = Mat4::IDENTITY;
-
-                let dx = if location.y % 2 == 0 { -1. } else { 0. };
-                mat = Mat4::from_translation(Vec3::new(
-                    (location.x as f32 + dx) * 0.5,
-                    location.y as f32 * TRIAG_HEIGHT - 0.6,
-                    0.,
-                )) * mat;
-
-                Transform::from_matrix(mat).with_scale(Vec3::splat(0.01))
-            }
-        }
+        let grid = self.grid();
+        let center = grid.center(*location);
+        let bottom = grid
+            .polygon(*location)
+            .into_iter()
+            .map(|p| p.y)
+            .fold(center.y, f32::min);
+
+        Transform::default()
+            .with_translation(Vec3::new(center.x, bottom - 0.1, 1.5))
+            .with_scale(Vec3::splat(0.01))
     }
 
     pub fn shape_transform(&self, location: &Location, z: f32) -> Transform {
-        match self.ty {
-            MapType::Octagons => {
-                let mut x = location.x as f32 + 0.5;
-                let y = location.y as f32 * 0.5 + 0.5;
-
-                let rot = if (location.y % 2).abs() == 1 {
-                    x += 0.5;
-                    0.
-                } else {
-                    std::f32::consts::PI / 8.
-                };
-
-                Transform::default()
-                    .with_rotation(Quat::from_rotation_z(rot))
-                    .with_translation(Vec3::new(x, y, z))
-            }
-            MapType::Hexagons => {
-                let mut x = location.x as f32 * 3.0;
-                let y = location.y as f32 * 0.866;
+        let grid = self.grid();
+        let center = grid.center(*location);
+        Transform::default()
+            .with_translation(Vec3::new(center.x, center.y, z))
+            .with_rotation(Quat::from_rotation_z(grid.rotation(*location)))
+    }
+}
 
-                if (location.y % 2).abs() == 1 {
-                    x += 1.5;
-                }
+/// Triangulates a convex `polygon` as a fan around `center`, positions relative to `center`.
+fn polygon_mesh(polygon: &[Point], center: Point) -> Mesh {
+    let (min_x, max_x, min_y, max_y) = polygon.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(a, b, c, d), p| (a.min(p.x), b.max(p.x), c.min(p.y), d.max(p.y)),
+    );
+    let uv = |p: &Point| {
+        [
+            (p.x - min_x) / (max_x - min_x),
+            1.0 - (p.y - min_y) / (max_y - min_y),
+        ]
+    };
 
-                Transform::default()
-                    .with_rotation(Quat::from_rotation_z(std::f32::consts::PI / 6.))
-                    .with_translation(Vec3::new(x, y, z))
-            }
-            MapType::Squares => Transform::default().with_translation(Vec3::new(
-                location.x as f32,
-                location.y as f32,
-                z,
-            )),
-            MapType::Triangles => {
-                let mut mat = Mat4::IDENTITY;
-
-                let dx = if location.y % 2 == 0 { -1. } else { 0. };
-                mat = Mat4::from_translation(Vec3::new(
-                    (location.x as f32 + dx) * 0.5,
-                    location.y as f32 * TRIAG_HEIGHT,
-                    z,
-                )) * mat;
-
-                if location.x % 2 == 1 || location.x % 2 == -1 {
-                    mat *= Mat4::from_rotation_z(std::f32::consts::PI);
-                }
-                Transform::from_matrix(mat)
-            }
-        }
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    let mut uvs = vec![uv(&center)];
+    for p in polygon {
+        positions.push([p.x - center.x, p.y - center.y, 0.0]);
+        uvs.push(uv(p));
     }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+    let n = polygon.len() as u32;
+    let indices = (0..n).flat_map(|i| [0, i + 1, (i + 1) % n + 1]).collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
 }
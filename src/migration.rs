@@ -0,0 +1,99 @@
+//! Save files carry a `version`, older ones are upgraded one version at a time on load.
+//!
+//! To change the format, bump [`VERSION`] and add a step to [`MIGRATIONS`] that turns a save of
+//! the previous version into the new one. Fixtures of every version live in `tests/fixtures`.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::scene::Scene;
+
+/// Version of the saves written now.
+pub const VERSION: u32 = 1;
+
+/// Step `i` upgrades a save of version `i` to version `i + 1`.
+const MIGRATIONS: [fn(&mut Value) -> Result<(), LoadError>; VERSION as usize] = [v0_to_v1];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// Not JSON at all.
+    Json(String),
+    /// `version` is there but not a number.
+    Version(Value),
+    /// Written by a newer mapbuilder, loading it could lose data.
+    Newer(u32),
+    /// The JSON does not look like a save of its version.
+    Format { version: u32, error: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Json(e) => write!(f, "Not a save file: {}", e),
+            LoadError::Version(v) => write!(f, "Unknown save version {}", v),
+            LoadError::Newer(v) => write!(
+                f,
+                "The save has version {}, this mapbuilder only reads up to version {}",
+                v, VERSION
+            ),
+            LoadError::Format { version, error } => {
+                write!(f, "Broken save of version {}: {}", version, error)
+            }
+        }
+    }
+}
+
+/// Version of `save`, files without one are from before versions were introduced.
+pub fn version(save: &Value) -> Result<u32, LoadError> {
+    match save.get("version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| LoadError::Version(v.clone())),
+    }
+}
+
+/// Upgrades `save` to [`VERSION`].
+pub fn migrate(mut save: Value) -> Result<Value, LoadError> {
+    let from = version(&save)?;
+    if from > VERSION {
+        return Err(LoadError::Newer(from));
+    }
+    for (v, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(&mut save)?;
+        save["version"] = (v as u32 + 1).into();
+    }
+    Ok(save)
+}
+
+/// Reads a save of any known version, with the version it had.
+pub fn load_scene(text: &str) -> Result<(Scene, u32), LoadError> {
+    let save: Value = serde_json::from_str(text).map_err(|e| LoadError::Json(e.to_string()))?;
+    let from = version(&save)?;
+    let scene = serde_json::from_value(migrate(save)?).map_err(|e| LoadError::Format {
+        version: from,
+        error: e.to_string(),
+    })?;
+    Ok((scene, from))
+}
+
+/// Saves without a version predate it. Everything added since then has a default, so only the
+/// version is added, once the save looks like one.
+fn v0_to_v1(save: &mut Value) -> Result<(), LoadError> {
+    let broken = |error: &str| LoadError::Format {
+        version: 0,
+        error: error.to_string(),
+    };
+    let save = save
+        .as_object_mut()
+        .ok_or_else(|| broken("not an object"))?;
+    if !save.get("config").is_some_and(Value::is_object) {
+        return Err(broken("no config"));
+    }
+    if !save.get("planets").is_some_and(Value::is_array) {
+        return Err(broken("no planets"));
+    }
+    Ok(())
+}
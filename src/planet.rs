@@ -1,15 +1,19 @@
 use bevy::prelude::DespawnRecursiveExt;
 use bevy::{
     prelude::*,
-    sprite::{ColorMaterial, MaterialMesh2dBundle},
+    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle},
 };
 use egui::Color32;
 use petname::Petnames;
 use serde::{Deserialize, Serialize};
 
+use crate::attributes::{attribute, AttributeValue, Attributes};
+pub use crate::geometry::Location;
 use crate::map_config::MapConfig;
 use crate::{eprintit, utils};
 
+/// Colors of neutral and the first players, later players get colors spread around the
+/// color wheel.
 pub const COLORS: [Color32; 7] = [
     Color32::GRAY,
     Color32::RED,
@@ -23,11 +27,13 @@ pub struct PlanetPlugin;
 impl Plugin for PlanetPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.insert_resource(Player(0))
+            .init_resource::<Players>()
             .add_event::<PlanetEvent>()
             .add_startup_system(setup)
             .add_system(align_planet_name)
             .add_system(handle_planet_events)
-            .add_system(change_planet_color)
+            .add_system(place_moved_planets.after(handle_planet_events))
+            .add_system(change_planet_color.after(handle_planet_events))
             .add_system(show_text_on_selected);
     }
 }
@@ -48,17 +54,112 @@ fn setup(
         .insert_bundle((HoverPlanet, Location { x: 0, y: 0 }));
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// Index into [`Players`], 0 is neutral.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub struct Player(pub usize);
 
 impl Player {
-    pub fn color(&self) -> Color {
-        let color = self.color32();
-        Color::rgba_u8(color.r(), color.g(), color.b(), color.a())
+    pub fn is_neutral(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    /// sRGB
+    pub color: [u8; 3],
+    /// Players with the same team play together.
+    pub team: usize,
+}
+
+/// Everyone that can own planets, the first entry is neutral.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Players {
+    pub players: Vec<PlayerInfo>,
+}
+
+impl Default for Players {
+    /// Neutral and six players, each on their own team.
+    fn default() -> Self {
+        let mut players = Players {
+            players: vec![PlayerInfo {
+                name: "Neutral".to_string(),
+                color: rgb(COLORS[0]),
+                team: 0,
+            }],
+        };
+        for _ in 1..COLORS.len() {
+            players.add();
+        }
+        players
+    }
+}
+
+fn rgb(color: Color32) -> [u8; 3] {
+    [color.r(), color.g(), color.b()]
+}
+
+impl Players {
+    /// Far more players than a game has, a map that asks for more is broken.
+    pub const MAX: usize = 64;
+
+    /// Players without neutral.
+    pub fn count(&self) -> usize {
+        self.players.len() - 1
+    }
+
+    pub fn get(&self, player: Player) -> Option<&PlayerInfo> {
+        self.players.get(player.0)
+    }
+
+    /// Every player, neutral first.
+    pub fn iter(&self) -> impl Iterator<Item = (Player, &PlayerInfo)> {
+        self.players
+            .iter()
+            .enumerate()
+            .map(|(i, info)| (Player(i), info))
+    }
+
+    /// Adds a player on a team of its own.
+    pub fn add(&mut self) -> Player {
+        let i = self.players.len();
+        let color = match COLORS.get(i) {
+            Some(color) => rgb(*color),
+            None => {
+                // the golden angle keeps neighbouring players apart
+                let c = Color::hsl((i as f32 * 137.5) % 360., 0.8, 0.5).as_rgba_f32();
+                [c[0], c[1], c[2]].map(|c| (c * 255.) as u8)
+            }
+        };
+        self.players.push(PlayerInfo {
+            name: format!("Player {}", i),
+            color,
+            team: i,
+        });
+        Player(i)
+    }
+
+    /// Unknown players, like the owners of planets from a bigger map, show up in magenta.
+    pub fn color32(&self, player: Player) -> Color32 {
+        match self.get(player) {
+            Some(info) => Color32::from_rgb(info.color[0], info.color[1], info.color[2]),
+            None => Color32::from_rgb(255, 0, 255),
+        }
+    }
+
+    pub fn color(&self, player: Player) -> Color {
+        let color = self.color32(player);
+        Color::rgb_u8(color.r(), color.g(), color.b())
     }
 
-    pub fn color32(&self) -> Color32 {
-        COLORS[self.0]
+    pub fn name(&self, player: Player) -> String {
+        match self.get(player) {
+            Some(info) => info.name.clone(),
+            None => format!("Player {}", player.0),
+        }
     }
 }
 
@@ -66,42 +167,73 @@ impl Player {
 pub struct PlanetMesh;
 #[derive(Component, Clone, Debug)]
 pub struct PlanetName;
+/// Border around a selected planet, a child of its `PlanetMesh`.
+#[derive(Component, Clone, Debug)]
+pub struct PlanetOutline;
 
-#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+/// How much bigger the outline is than the planet.
+const OUTLINE_SCALE: f32 = 1.15;
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct PlanetData {
     pub player: Player,
     pub name: String,
     pub ship_count: usize,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    pub attributes: Attributes,
 }
 
 #[derive(Component, Clone, Debug)]
 pub struct PlanetEntity {
     pub name: Entity,
     pub mesh: Entity,
+    pub outline: Entity,
 }
 
 #[derive(Component, Debug, Default)]
 pub struct HoverPlanet;
 
-#[derive(
-    Component, Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord,
-)]
-pub struct Location {
-    pub x: i32,
-    pub y: i32,
-}
-
 #[derive(Component, Clone, Debug)]
 pub struct Selected(pub bool);
 
+#[derive(Clone, Debug)]
 pub enum PlanetEvent {
-    Create { loc: Location, player: Player },
-    CreateNamed { loc: Location, data: PlanetData },
-    Delete { id: Entity },
-    SetPlayer { id: Entity, player: Player },
-    SetName { id: Entity, name: String },
-    SetShipCount { id: Entity, amount: usize },
-    SetSelected { id: Entity, selected: bool },
+    Create {
+        loc: Location,
+        player: Player,
+    },
+    CreateNamed {
+        loc: Location,
+        data: PlanetData,
+    },
+    Delete {
+        id: Entity,
+    },
+    SetPlayer {
+        id: Entity,
+        player: Player,
+    },
+    SetName {
+        id: Entity,
+        name: String,
+    },
+    SetShipCount {
+        id: Entity,
+        amount: usize,
+    },
+    SetSelected {
+        id: Entity,
+        selected: bool,
+    },
+    Move {
+        id: Entity,
+        to: Location,
+    },
+    SetAttribute {
+        id: Entity,
+        key: &'static str,
+        value: AttributeValue,
+    },
 }
 
 fn show_text_on_selected(
@@ -109,58 +241,87 @@ fn show_text_on_selected(
     mut visibles: Query<&mut Visibility>,
 ) {
     for (p, s) in planets.iter() {
-        let mut vis = visibles.get_mut(p.name).unwrap();
-        vis.is_visible = s.0;
+        for id in [p.name, p.outline] {
+            let mut vis = visibles.get_mut(id).unwrap();
+            vis.is_visible = s.0;
+        }
     }
 }
 
 #[allow(clippy::too_many_arguments)]
-fn handle_planet_events(
+pub(crate) fn handle_planet_events(
     mut event_reader: EventReader<PlanetEvent>,
-    mut planets: Query<(&mut PlanetData, &mut Selected)>,
+    mut planets: Query<(&mut PlanetData, &mut Selected, &mut Location)>,
     mut commands: Commands,
     mut materials: ResMut<Assets<ColorMaterial>>,
     names: Res<Petnames<'static>>,
     mut rng: ResMut<utils::rng::RNG>,
     config: Res<MapConfig>,
+    players: Res<Players>,
 ) {
+    // saves, pastes and symmetric counterparts can point past the edge of a bounded grid
+    let grid = config.grid();
+    let on_grid = |loc: &Location| {
+        let ok = grid.is_cell(*loc);
+        if !ok {
+            eprintit!("Not a cell of the map: {:?}", loc);
+        }
+        ok
+    };
     for event in event_reader.iter() {
         match event {
+            PlanetEvent::Create { loc, .. }
+            | PlanetEvent::CreateNamed { loc, .. }
+            | PlanetEvent::Move { to: loc, .. }
+                if !on_grid(loc) => {}
             PlanetEvent::Create { loc, player } => {
                 let data = PlanetData {
                     player: *player,
                     ship_count: 10,
                     name: names.generate(rng.as_mut(), 2, " "),
+                    ..default()
                 };
 
-                spawn_named_planet(&config, &mut commands, data, *loc, &mut materials);
+                let color = players.color(data.player);
+                spawn_named_planet(&config, &mut commands, data, *loc, color, &mut materials);
             }
             PlanetEvent::CreateNamed { data, loc } => {
-                spawn_named_planet(&config, &mut commands, data.clone(), *loc, &mut materials);
+                let (data, color) = (data.clone(), players.color(data.player));
+                spawn_named_planet(&config, &mut commands, data, *loc, color, &mut materials);
             }
             PlanetEvent::Delete { id } => {
                 commands.entity(*id).despawn_recursive();
             }
             PlanetEvent::SetPlayer { id, player } => {
-                if let Ok((mut data, _)) = planets.get_mut(*id) {
+                if let Ok((mut data, _, _)) = planets.get_mut(*id) {
                     data.player = *player;
                 }
             }
             PlanetEvent::SetName { id, name } => {
-                if let Ok((mut data, _)) = planets.get_mut(*id) {
+                if let Ok((mut data, _, _)) = planets.get_mut(*id) {
                     data.name = name.clone();
                 }
             }
             PlanetEvent::SetShipCount { id, amount } => {
-                if let Ok((mut data, _)) = planets.get_mut(*id) {
+                if let Ok((mut data, _, _)) = planets.get_mut(*id) {
                     data.ship_count = *amount;
                 }
             }
             PlanetEvent::SetSelected { id, selected } => {
-                if let Ok((_, mut s)) = planets.get_mut(*id) {
+                if let Ok((_, mut s, _)) = planets.get_mut(*id) {
                     s.0 = *selected;
                 }
             }
+            PlanetEvent::Move { id, to } => {
+                if let Ok((_, _, mut loc)) = planets.get_mut(*id) {
+                    *loc = *to;
+                }
+            }
+            PlanetEvent::SetAttribute { id, key, value } => {
+                if let (Ok((mut data, _, _)), Some(attr)) = (planets.get_mut(*id), attribute(key)) {
+                    data.attributes.set(attr, value.clone());
+                }
+            }
         }
     }
 }
@@ -170,10 +331,9 @@ fn spawn_named_planet(
     commands: &mut Commands,
     data: PlanetData,
     loc: Location,
-
+    color: Color,
     materials: &mut Assets<ColorMaterial>,
 ) {
-    let color = data.player.color();
     let transform = config.text_transform(&loc);
     let name = commands
         .spawn_bundle(Text2dBundle {
@@ -193,6 +353,22 @@ fn spawn_named_planet(
         .insert(PlanetName)
         .id();
 
+    let outline = commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: config.mesh(&loc).into(),
+            material: materials.add(ColorMaterial::from(Color::rgb_u8(255, 128, 0))),
+            // behind the planet, it only shows around the edges
+            transform: Transform::from_xyz(0., 0., -0.1).with_scale(Vec3::new(
+                OUTLINE_SCALE,
+                OUTLINE_SCALE,
+                1.,
+            )),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(PlanetOutline)
+        .id();
+
     let transform = config.shape_transform(&loc, 0.5);
     let mesh = commands
         .spawn_bundle(MaterialMesh2dBundle {
@@ -202,6 +378,7 @@ fn spawn_named_planet(
             ..default()
         })
         .insert(PlanetMesh)
+        .add_child(outline)
         .id();
 
     commands
@@ -215,17 +392,48 @@ fn spawn_named_planet(
         .insert(Selected(false))
         .add_child(name)
         .add_child(mesh)
-        .insert(PlanetEntity { name, mesh });
+        .insert(PlanetEntity {
+            name,
+            mesh,
+            outline,
+        });
+}
+
+/// Puts the meshes and name of moved planets on their new cell.
+fn place_moved_planets(
+    config: Res<MapConfig>,
+    planets: Query<(&Location, &PlanetEntity), Changed<Location>>,
+    mut meshes: Query<(&mut Transform, &mut Mesh2dHandle), With<PlanetMesh>>,
+    mut outlines: Query<&mut Mesh2dHandle, (With<PlanetOutline>, Without<PlanetMesh>)>,
+    mut names: Query<&mut Transform, (With<PlanetName>, Without<PlanetMesh>)>,
+) {
+    for (loc, e) in planets.iter() {
+        if let Ok((mut t, mut mesh)) = meshes.get_mut(e.mesh) {
+            *t = config.shape_transform(loc, 0.5);
+            *mesh = config.mesh(loc).into();
+        }
+        if let Ok(mut mesh) = outlines.get_mut(e.outline) {
+            *mesh = config.mesh(loc).into();
+        }
+        if let Ok(mut t) = names.get_mut(e.name) {
+            *t = config.text_transform(loc);
+        }
+    }
 }
 
 fn change_planet_color(
-    planets: Query<(&PlanetData, &PlanetEntity), Changed<PlanetData>>,
+    planets: Query<(&PlanetData, &PlanetEntity, ChangeTrackers<PlanetData>)>,
     meshes_query: Query<&Handle<ColorMaterial>, With<PlanetMesh>>,
     mut meshes: ResMut<Assets<ColorMaterial>>,
+    players: Res<Players>,
 ) {
-    for (d, e) in planets.into_iter() {
+    // a new color for a player repaints all planets
+    let planets = planets
+        .iter()
+        .filter(|(_, _, t)| players.is_changed() || t.is_changed());
+    for (d, e, _) in planets {
         if let Ok(h) = meshes_query.get(e.mesh) {
-            meshes.set_untracked(h, ColorMaterial::from(d.player.color()));
+            meshes.set_untracked(h, ColorMaterial::from(players.color(d.player)));
         }
     }
 }
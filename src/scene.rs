@@ -1,114 +1,140 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 
 use crate::{
     eprintit,
+    export::{ExportFormat, ExportMap, ExportOptions, PlanetWars},
+    geometry::{symmetry::Symmetry, Grid, GridParams},
+    history::HistoryEvent,
+    import::{import, ImportError, ImportReport},
     map_config::{MapConfig, MapEvent, MapType},
-    planet::{Location, PlanetData, PlanetEvent},
+    migration::{load_scene, LoadError, VERSION},
+    planet::{Location, PlanetData, PlanetEvent, Players},
+    upload::{UploadEvent, UploadResult},
+    validation::{validate, Severity},
 };
 
 pub enum SceneEvent {
     Save,
     Load,
     LoadCont(String),
+    /// Picks a map exported for planetwars and puts its planets on a grid of type `ty`, see
+    /// [`crate::import`].
+    Import {
+        ty: MapType,
+        spacing: f32,
+    },
     Export {
-        girth: f32,
-        name: String,
+        options: ExportOptions,
+        format: &'static dyn ExportFormat,
     },
+    /// Sends the [`PlanetWars`] export to `url`.
     Upload {
-        girth: f32,
-        name: String,
+        options: ExportOptions,
         url: String,
     },
 }
 
+/// How the last load went, for the editor to show.
+#[derive(Default)]
+pub struct LoadReport {
+    pub error: Option<LoadError>,
+    /// The save was of an older version and has been upgraded.
+    pub upgraded_from: Option<u32>,
+    /// The last load was an import of a planetwars map.
+    pub import: Option<Result<ImportReport, ImportError>>,
+}
+
+/// Name of the map in the export, restored by an import.
+#[derive(Default)]
+pub struct MapName(pub String);
+
 pub struct ScenePlugin;
 impl Plugin for ScenePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SceneEvent>()
+            .add_event::<UploadEvent>()
+            .init_resource::<LoadReport>()
+            .init_resource::<MapName>()
             .add_plugin(io::IOPlugin)
             .add_system(handle_scene_events);
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct ScenePlanet {
-    data: PlanetData,
-    location: Location,
-}
-#[derive(Serialize, Deserialize)]
-struct SceneConfig {
-    ty: MapType,
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenePlanet {
+    pub data: PlanetData,
+    pub location: Location,
 }
-
-#[derive(Serialize, Deserialize)]
-struct Scene {
-    config: SceneConfig,
-    planets: Vec<ScenePlanet>,
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneConfig {
+    pub ty: MapType,
+    #[serde(flatten)]
+    pub params: GridParams,
+    #[serde(default)]
+    pub symmetry: Symmetry,
 }
 
-#[derive(Serialize)]
-struct Planet<'a> {
-    name: &'a str,
-    x: f32,
-    y: f32,
-    owner: Option<usize>,
-    ship_count: usize,
-}
-
-fn get_planets_export(
-    dist: f32,
-    planets: &Query<(&PlanetData, &Location, Entity)>,
-    current_config: &MapConfig,
-    name: &str,
-) -> Value {
-    let mut longest_dist = 0.0;
-    for (_, l1, _) in planets {
-        let t1 = current_config.shape_transform(l1, 0.);
-        for (_, l2, _) in planets {
-            let t2 = current_config.shape_transform(l2, 0.);
-            let d = (t1.translation.x - t2.translation.x).powi(2)
-                + (t1.translation.y - t2.translation.y).powi(2);
-            if d > longest_dist {
-                longest_dist = d;
-            }
+impl SceneConfig {
+    pub fn new(config: &MapConfig) -> Self {
+        Self {
+            ty: config.ty,
+            params: config.params,
+            symmetry: config.symmetry,
         }
     }
-    longest_dist = longest_dist.sqrt();
 
-    let scale = dist / longest_dist;
+    /// The grid the locations of the scene are on.
+    pub fn grid(&self) -> Box<dyn Grid + Send + Sync> {
+        self.ty.grid_with(&self.params)
+    }
+}
 
-    let planets: Vec<_> = planets
-        .iter()
-        .map(|(data, loc, _)| {
-            let t1 = current_config.shape_transform(loc, 0.);
-            let x = t1.translation.x;
-            let y = t1.translation.y;
-
-            let owner = (data.player.0 != 0).then_some(data.player.0);
-
-            Planet {
-                name: &data.name,
-                x: x * scale,
-                y: y * scale,
-                owner,
-                ship_count: data.ship_count,
-            }
-        })
-        .collect();
+/// A saved map, the clipboard holds one with only the copied planets.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scene {
+    /// Format version, see [`crate::migration`].
+    #[serde(default)]
+    pub version: u32,
+    pub config: SceneConfig,
+    pub planets: Vec<ScenePlanet>,
+    /// Older saves and the clipboard have none, loading them keeps the default players.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players: Option<Players>,
+}
 
-    serde_json::json!({ "planets": planets, "name": name })
+/// Whether the map passes validation, the errors are printed when it does not.
+fn exportable(
+    girth: f32,
+    planets: &[(Location, &PlanetData)],
+    grid: &dyn Grid,
+    players: &Players,
+) -> bool {
+    let issues = validate(planets, grid, players, Some(girth));
+    let mut ok = true;
+    for issue in issues.iter().filter(|i| i.severity() == Severity::Error) {
+        eprintit!("Can not export: {}", issue.message);
+        ok = false;
+    }
+    ok
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_scene_events(
     mut commands: Commands,
     planets: Query<(&PlanetData, &Location, Entity)>,
     current_config: Res<MapConfig>,
+    mut players: ResMut<Players>,
+    mut report: ResMut<LoadReport>,
+    mut map_name: ResMut<MapName>,
     mut events: EventReader<SceneEvent>,
+    // settings of an import whose file has not been picked yet
+    mut pending_import: Local<Option<(MapType, f32)>>,
 
     mut map_events: EventWriter<MapEvent>,
     mut planet_events: EventWriter<PlanetEvent>,
+    mut upload_events: EventWriter<UploadEvent>,
+    mut history_events: EventWriter<HistoryEvent>,
 ) {
     for event in events.iter() {
         match event {
@@ -120,97 +146,255 @@ fn handle_scene_events(
                         location: *y,
                     })
                     .collect();
-                let scene_config = SceneConfig {
-                    ty: current_config.ty,
-                };
-
                 let scene = Scene {
+                    version: VERSION,
                     planets,
-                    config: scene_config,
+                    config: SceneConfig::new(&current_config),
+                    players: Some(players.clone()),
                 };
                 let data = serde_json::to_string_pretty(&scene).unwrap();
 
                 io::save(data);
             }
-            SceneEvent::Export { girth, name } => {
-                let content =
-                    get_planets_export(*girth, &planets, &current_config, &name).to_string();
-                io::export(content);
+            SceneEvent::Export { options, format } => {
+                let config = SceneConfig::new(&current_config);
+                let planets: Vec<_> = planets.iter().map(|(d, l, _)| (*l, d)).collect();
+                if !exportable(options.girth, &planets, config.grid().as_ref(), &players) {
+                    continue;
+                }
+                let content = format.export(&ExportMap {
+                    planets: &planets,
+                    config: &config,
+                    players: &players,
+                    options,
+                });
+                io::export(content, &options.name, format.extension());
             }
             SceneEvent::Load => {
+                *pending_import = None;
                 if let Some(data) = io::load() {
                     load_cont(
                         &data,
                         &planets,
+                        &mut players,
+                        &mut report,
                         &mut commands,
                         &mut map_events,
                         &mut planet_events,
+                        &mut history_events,
                     );
                 }
             }
-            SceneEvent::LoadCont(data) => load_cont(
-                data,
-                &planets,
-                &mut commands,
-                &mut map_events,
-                &mut planet_events,
-            ),
-            SceneEvent::Upload { girth, url, name } => {
-                let content =
-                    get_planets_export(*girth, &planets, &current_config, &name).to_string();
+            // on the web the picked file comes back later as `LoadCont`
+            SceneEvent::Import { ty, spacing } => {
+                *pending_import = Some((*ty, *spacing));
+                if let Some(data) = io::load() {
+                    *pending_import = None;
+                    import_cont(
+                        &data,
+                        (*ty, *spacing),
+                        &current_config,
+                        &planets,
+                        &mut players,
+                        &mut report,
+                        &mut map_name,
+                        &mut commands,
+                        &mut map_events,
+                        &mut planet_events,
+                        &mut history_events,
+                    );
+                }
+            }
+            SceneEvent::LoadCont(data) => match pending_import.take() {
+                Some(settings) => import_cont(
+                    data,
+                    settings,
+                    &current_config,
+                    &planets,
+                    &mut players,
+                    &mut report,
+                    &mut map_name,
+                    &mut commands,
+                    &mut map_events,
+                    &mut planet_events,
+                    &mut history_events,
+                ),
+                None => load_cont(
+                    data,
+                    &planets,
+                    &mut players,
+                    &mut report,
+                    &mut commands,
+                    &mut map_events,
+                    &mut planet_events,
+                    &mut history_events,
+                ),
+            },
+            SceneEvent::Upload { options, url } => {
+                let config = SceneConfig::new(&current_config);
+                let planets: Vec<_> = planets.iter().map(|(d, l, _)| (*l, d)).collect();
+                if !exportable(options.girth, &planets, config.grid().as_ref(), &players) {
+                    upload_events.send(UploadEvent(UploadResult::Failed(
+                        "the map has problems".to_string(),
+                    )));
+                    continue;
+                }
+                let content = PlanetWars::json(&ExportMap {
+                    planets: &planets,
+                    config: &config,
+                    players: &players,
+                    options,
+                })
+                .to_string();
                 io::upload(url, content);
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn load_cont(
     data: &str,
     planets: &Query<(&PlanetData, &Location, Entity)>,
+    players: &mut Players,
+    report: &mut LoadReport,
+    commands: &mut Commands,
+    map_events: &mut EventWriter<MapEvent>,
+    planet_events: &mut EventWriter<PlanetEvent>,
+    history_events: &mut EventWriter<HistoryEvent>,
+) {
+    let (scene, from) = match load_scene(data) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintit!("Error: {}", e);
+            *report = LoadReport {
+                error: Some(e),
+                ..default()
+            };
+            return;
+        }
+    };
+    *report = LoadReport {
+        upgraded_from: (from < VERSION).then_some(from),
+        ..default()
+    };
+
+    replace_scene(
+        scene,
+        planets,
+        players,
+        commands,
+        map_events,
+        planet_events,
+        history_events,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn import_cont(
+    data: &str,
+    (ty, spacing): (MapType, f32),
+    current_config: &MapConfig,
+    planets: &Query<(&PlanetData, &Location, Entity)>,
+    players: &mut Players,
+    report: &mut LoadReport,
+    map_name: &mut MapName,
     commands: &mut Commands,
     map_events: &mut EventWriter<MapEvent>,
     planet_events: &mut EventWriter<PlanetEvent>,
+    history_events: &mut EventWriter<HistoryEvent>,
 ) {
-    let Scene {
-        planets: p2,
-        config,
-    } = match serde_json::from_str(data) {
+    let imported = match import(data, ty, current_config.params, spacing) {
         Ok(x) => x,
         Err(e) => {
             eprintit!("Error: {}", e);
+            *report = LoadReport {
+                import: Some(Err(e)),
+                ..default()
+            };
             return;
         }
     };
+    *report = LoadReport {
+        import: Some(Ok(imported.report)),
+        ..default()
+    };
+    map_name.0 = imported.name;
+
+    replace_scene(
+        imported.scene,
+        planets,
+        players,
+        commands,
+        map_events,
+        planet_events,
+        history_events,
+    );
+}
 
+/// Removes every planet and puts the ones of `scene` in their place, which can not be undone.
+fn replace_scene(
+    scene: Scene,
+    planets: &Query<(&PlanetData, &Location, Entity)>,
+    players: &mut Players,
+    commands: &mut Commands,
+    map_events: &mut EventWriter<MapEvent>,
+    planet_events: &mut EventWriter<PlanetEvent>,
+    history_events: &mut EventWriter<HistoryEvent>,
+) {
     planets
         .iter()
         .map(|(_, _, e)| e)
         .for_each(|e| commands.entity(e).despawn_recursive());
-    map_events.send(MapEvent::SetType(config.ty));
-    planet_events.send_batch(p2.into_iter().map(|ScenePlanet { data, location }| {
-        PlanetEvent::CreateNamed {
-            loc: location,
-            data,
-        }
-    }));
+    history_events.send(HistoryEvent::Clear);
+    *players = scene.players.unwrap_or_default();
+    map_events.send(MapEvent::SetType(scene.config.ty));
+    map_events.send(MapEvent::SetParams(scene.config.params));
+    map_events.send(MapEvent::SetSymmetry(scene.config.symmetry));
+    planet_events.send_batch(
+        scene
+            .planets
+            .into_iter()
+            .map(|ScenePlanet { data, location }| PlanetEvent::CreateNamed {
+                loc: location,
+                data,
+            }),
+    );
 }
 
 #[cfg(not(target_family = "wasm"))]
 mod io {
-    use bevy::{prelude::Plugin, tasks::IoTaskPool};
+    use bevy::{
+        prelude::{EventWriter, Plugin},
+        tasks::IoTaskPool,
+    };
     use rfd::{AsyncFileDialog, FileDialog};
 
     use std::{
         fs::File,
         io::{Read, Write},
         path::Path,
+        sync::Mutex,
     };
 
+    use crate::upload::{post, UploadEvent, UploadResult};
+
     pub struct IOPlugin;
     impl Plugin for IOPlugin {
-        fn build(&self, _app: &mut bevy::prelude::App) {}
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_system(complete_upload);
+        }
     }
 
+    fn complete_upload(mut ev: EventWriter<UploadEvent>) {
+        if let Ok(mut x) = UPLOADS.lock() {
+            ev.send_batch(x.drain(..).map(UploadEvent));
+        }
+    }
+
+    /// Results of the uploads that finished since the last frame.
+    static UPLOADS: Mutex<Vec<UploadResult>> = Mutex::new(Vec::new());
+
     pub fn write_to_file(contents: &[u8], location: &Path) -> Result<(), std::io::Error> {
         let mut file = File::options()
             .create(true)
@@ -248,19 +432,31 @@ mod io {
         }
     }
 
-    pub fn export(content: String) {
+    pub fn export(content: Vec<u8>, name: &str, extension: &'static str) {
+        let dialog = AsyncFileDialog::new()
+            .add_filter(extension, &[extension])
+            .set_file_name(&format!("{}.{}", name, extension));
         let thread_pool = IoTaskPool::get();
         thread_pool
             .spawn(async move {
-                if let Some(path) = AsyncFileDialog::new().save_file().await {
-                    write_to_file(content.as_bytes(), path.path()).unwrap();
+                if let Some(path) = dialog.save_file().await {
+                    write_to_file(&content, path.path()).unwrap();
                 }
             })
             .detach();
     }
 
     pub fn upload(url: &str, content: String) {
-        todo!()
+        let url = url.to_string();
+        let thread_pool = IoTaskPool::get();
+        thread_pool
+            .spawn(async move {
+                let result = post(&url, &content);
+                if let Ok(mut x) = UPLOADS.lock() {
+                    x.push(result);
+                }
+            })
+            .detach();
     }
 }
 
@@ -272,6 +468,7 @@ mod io {
     use wasm_bindgen::prelude::wasm_bindgen;
 
     use super::SceneEvent;
+    use crate::upload::{UploadEvent, UploadResult};
 
     mod js {
         use wasm_bindgen::prelude::wasm_bindgen;
@@ -288,7 +485,7 @@ mod io {
             pub fn load();
 
             #[wasm_bindgen(js_namespace = ["window", "scene"])]
-            pub fn exp(s: &str);
+            pub fn exp(content: &[u8], name: &str);
 
             #[wasm_bindgen(js_namespace = ["window", "scene"])]
             pub fn upload(url: &str, content: &str);
@@ -298,7 +495,15 @@ mod io {
     pub struct IOPlugin;
     impl Plugin for IOPlugin {
         fn build(&self, app: &mut bevy::prelude::App) {
-            app.add_system(complete_load);
+            app.add_system(complete_load).add_system(complete_upload);
+        }
+    }
+
+    fn complete_upload(mut ev: EventWriter<UploadEvent>) {
+        if let Ok(mut x) = UPLOAD.lock() {
+            if let Some(result) = x.take() {
+                ev.send(UploadEvent(result));
+            }
         }
     }
 
@@ -328,11 +533,29 @@ mod io {
         }
     }
 
-    pub fn export(content: String) {
-        js::exp(&content);
+    pub fn export(content: Vec<u8>, name: &str, extension: &'static str) {
+        js::exp(&content, &format!("{}.{}", name, extension));
     }
 
+    static UPLOAD: Mutex<Option<UploadResult>> = Mutex::new(None);
+
     pub fn upload(url: &str, content: String) {
         js::upload(url, &content);
     }
+
+    /// `status` is 0 when the server could not be reached, `body` is the error then.
+    #[wasm_bindgen]
+    pub fn finish_upload(status: u16, body: &str) {
+        let result = match status {
+            0 => UploadResult::Failed(body.to_string()),
+            200..=399 => UploadResult::Done(status),
+            _ => UploadResult::Refused {
+                status,
+                body: body.to_string(),
+            },
+        };
+        if let Ok(mut x) = UPLOAD.lock() {
+            *x = Some(result);
+        }
+    }
 }
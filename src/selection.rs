@@ -0,0 +1,164 @@
+//! Selecting several planets at once, by dragging a box or shift and ctrl clicking, and
+//! dragging them to other cells.
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::geometry::{Grid, Location, Point};
+use crate::map_config::MapConfig;
+use crate::planet::{HoverPlanet, PlanetData};
+
+/// The box being dragged out with the left mouse button, in world coordinates.
+#[derive(Default)]
+pub struct SelectionBox {
+    /// Where the drag started, `None` while there is no box.
+    pub start: Option<Point>,
+    pub end: Point,
+}
+
+/// Where the planets being dragged or pasted would land, the hover planet shows the one under the mouse.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MovePreview {
+    /// Target cells in the order of the dragged planets, empty while nothing is dragged.
+    pub targets: Vec<Location>,
+    /// A planet would land on a taken cell or off the grid, dropping does nothing.
+    pub blocked: bool,
+}
+
+#[derive(Component)]
+struct SelectionBoxSprite;
+
+#[derive(Component)]
+struct MoveGhost;
+
+pub struct SelectionPlugin;
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionBox>()
+            .init_resource::<MovePreview>()
+            .add_startup_system(setup)
+            .add_system(draw_box)
+            .add_system(draw_ghosts);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1., 0.5, 0., 0.2),
+                custom_size: Some(Vec2::ONE),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(SelectionBoxSprite);
+}
+
+fn draw_box(
+    selection: Res<SelectionBox>,
+    mut sprites: Query<(&mut Transform, &mut Visibility), With<SelectionBoxSprite>>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    for (mut transform, mut visibility) in sprites.iter_mut() {
+        visibility.is_visible = selection.start.is_some();
+        if let Some(start) = selection.start {
+            let end = selection.end;
+            transform.translation = Vec3::new((start.x + end.x) / 2., (start.y + end.y) / 2., 3.);
+            transform.scale = Vec3::new((end.x - start.x).abs(), (end.y - start.y).abs(), 1.);
+        }
+    }
+}
+
+fn ghost_color(blocked: bool) -> Color {
+    let mut color = if blocked { Color::RED } else { Color::PURPLE };
+    color.set_a(0.4);
+    color
+}
+
+/// Ghosts on the target cells, red while the drop is blocked.
+fn draw_ghosts(
+    preview: Res<MovePreview>,
+    config: Res<MapConfig>,
+    ghosts: Query<Entity, With<MoveGhost>>,
+    hover: Query<(&Handle<ColorMaterial>, &Location), With<HoverPlanet>>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !preview.is_changed() {
+        return;
+    }
+    for ghost in ghosts.iter() {
+        commands.entity(ghost).despawn();
+    }
+    let color = ghost_color(preview.blocked);
+    let mut under_mouse = None;
+    for (handle, loc) in hover.iter() {
+        materials.set_untracked(handle, ColorMaterial::from(color));
+        under_mouse = Some(*loc);
+    }
+
+    let material = materials.add(ColorMaterial::from(color));
+    for loc in preview.targets.iter().filter(|l| Some(**l) != under_mouse) {
+        commands
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: config.mesh(loc).into(),
+                material: material.clone(),
+                transform: config.shape_transform(loc, 0.1),
+                ..default()
+            })
+            .insert(MoveGhost);
+    }
+}
+
+/// Where the planets on `moving` end up when the one on `from` is dragged to `to`. They all
+/// shift by the same distance and snap to the nearest cell, planets on `occupied` stay put.
+pub fn plan_move(
+    grid: &dyn Grid,
+    from: Location,
+    to: Location,
+    moving: &[Location],
+    occupied: &[Location],
+) -> MovePreview {
+    let (a, b) = (grid.center(from), grid.center(to));
+    let points: Vec<_> = moving
+        .iter()
+        .map(|loc| {
+            let p = grid.center(*loc);
+            Point::new(p.x + b.x - a.x, p.y + b.y - a.y)
+        })
+        .collect();
+    plan_drop(grid, &points, occupied)
+}
+
+/// Snaps planets dropped at `points` to their cells, blocked when one misses the grid or two
+/// planets share a cell.
+pub fn plan_drop(grid: &dyn Grid, points: &[Point], occupied: &[Location]) -> MovePreview {
+    let mut preview = MovePreview::default();
+    for p in points {
+        match grid.cell_at(*p).filter(|l| grid.is_cell(*l)) {
+            Some(target) => {
+                preview.blocked |= occupied.contains(&target) || preview.targets.contains(&target);
+                preview.targets.push(target);
+            }
+            None => preview.blocked = true,
+        }
+    }
+    preview
+}
+
+/// Whether `p` lies in the box with corners `a` and `b`.
+pub fn within(a: Point, b: Point, p: Point) -> bool {
+    (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x) && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y)
+}
+
+/// Name for the `index`th (from 1) selected planet. `{n}` is replaced by the index, `{name}`
+/// by the current name and `{player}` by the owner.
+pub fn rename(pattern: &str, index: usize, data: &PlanetData) -> String {
+    pattern
+        .replace("{n}", &index.to_string())
+        .replace("{name}", &data.name)
+        .replace("{player}", &data.player.0.to_string())
+}
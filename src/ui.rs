@@ -1,7 +1,21 @@
 use crate::{
-    map_config::{MapConfig, MapEvent, MapType},
-    planet::{HoverPlanet, Location, PlanetData, PlanetEvent, Player, Selected, COLORS},
-    scene::SceneEvent,
+    analysis::{analyze, Analysis},
+    attributes::{Attribute, AttributeKind, AttributeValue, ATTRIBUTES},
+    export::{svg::Svg, ExportFormat, ExportOptions, FORMATS},
+    generator::{generate, GenerateError, GeneratorSettings},
+    geometry::{
+        polar::PolarGrid,
+        symmetry::{Symmetry, SymmetryKind},
+        GridParams, HexOrientation, OctagonGrid, TriangleOrientation,
+    },
+    history::{History, HistoryEvent},
+    map_config::{ConversionReport, MapConfig, MapEvent, MapType},
+    migration::VERSION,
+    planet::{HoverPlanet, Location, PlanetData, PlanetEvent, Player, Players, Selected},
+    scene::{LoadReport, MapName, SceneEvent},
+    selection::rename,
+    upload::{UploadEvent, UploadResult},
+    validation::{Severity, Validation},
     HoveringUI, ZEUS,
 };
 use bevy::prelude::*;
@@ -11,10 +25,11 @@ use egui::{
     Widget, WidgetWithState,
 };
 // use rfd::FileDialog;
+use rand::Rng;
 use std::{
+    collections::{HashMap, HashSet},
     hash::Hash,
-    ops::DerefMut,
-    sync::mpsc::{sync_channel, Receiver, SyncSender},
+    ops::RangeInclusive,
 };
 
 use crate::FPS;
@@ -24,6 +39,8 @@ impl Plugin for UIPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_system(ui_editor.before(ui_system))
             .add_system(ui_system)
+            .add_system(load_report_ui.after(ui_editor))
+            .add_system(upload_report_ui.after(ui_editor))
             .init_resource::<Icons>()
             .add_startup_system(load_images)
             .add_startup_system(set_font_sizes)
@@ -34,12 +51,22 @@ impl Plugin for UIPlugin {
 #[derive(Default)]
 struct Icons {
     handles: Vec<Handle<Image>>,
-    squares: TextureId,
-    triangles: TextureId,
-    hexagons: TextureId,
-    octagons: TextureId,
+    map_types: Vec<(MapType, TextureId)>,
 }
 
+/// Map types in the order they show up in the bottom bar, with their icon.
+const MAP_TYPE_ICONS: [(MapType, &str); 9] = [
+    (MapType::Triangles, "icons/triangle.png"),
+    (MapType::Squares, "icons/square.png"),
+    (MapType::Hexagons, "icons/hexagon.png"),
+    (MapType::Octagons, "icons/octagon.png"),
+    (MapType::Rhombille, "icons/rhombille.png"),
+    (MapType::Cairo, "icons/cairo.png"),
+    (MapType::SnubSquare, "icons/snub_square.png"),
+    (MapType::Free, "icons/free.png"),
+    (MapType::Polar, "icons/polar.png"),
+];
+
 fn set_font_sizes(mut egui_context: ResMut<EguiContext>) {
     use egui::FontFamily::Proportional;
     use egui::FontId;
@@ -63,20 +90,13 @@ fn load_images(
     mut icons: ResMut<Icons>,
     mut ctx: ResMut<EguiContext>,
 ) {
-    let sq = asset_server.load("icons/square.png");
-    icons.squares = ctx.add_image(sq.clone_weak());
-    icons.handles.push(sq);
-    let tri = asset_server.load("icons/triangle.png");
-    icons.triangles = ctx.add_image(tri.clone_weak());
-    icons.handles.push(tri);
-
-    let tri = asset_server.load("icons/hexagon.png");
-    icons.hexagons = ctx.add_image(tri.clone_weak());
-    icons.handles.push(tri);
-
-    let tri = asset_server.load("icons/octagon.png");
-    icons.octagons = ctx.add_image(tri.clone_weak());
-    icons.handles.push(tri);
+    for (ty, path) in MAP_TYPE_ICONS {
+        let handle = asset_server.load(path);
+        icons
+            .map_types
+            .push((ty, ctx.add_image(handle.clone_weak())));
+        icons.handles.push(handle);
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -143,6 +163,10 @@ struct PlanetWidget<'a, 'w, 's> {
     data: &'a PlanetData,
     loc: &'a Location,
     entity: Entity,
+    /// Symmetric copies of this planet with their index in `symmetry`, edits go to them too.
+    counterparts: Vec<(Entity, usize)>,
+    symmetry: &'a Symmetry,
+    players: &'a Players,
     events: &'a mut EventWriter<'w, 's, PlanetEvent>,
 }
 
@@ -152,6 +176,7 @@ impl<'a, 'w, 's> Widget for PlanetWidget<'a, 'w, 's> {
             ref player,
             ref name,
             ref ship_count,
+            ref attributes,
         } = self.data;
 
         let mut name = name.clone();
@@ -179,6 +204,11 @@ impl<'a, 'w, 's> Widget for PlanetWidget<'a, 'w, 's> {
                         id: self.entity,
                         amount,
                     });
+                    self.events.send_batch(
+                        self.counterparts
+                            .iter()
+                            .map(|(id, _)| PlanetEvent::SetShipCount { id: *id, amount }),
+                    );
                 } else {
                     let (response, painter) = ui.allocate_painter(
                         Vec2::splat(64.),
@@ -199,10 +229,30 @@ impl<'a, 'w, 's> Widget for PlanetWidget<'a, 'w, 's> {
 
         ui.label(format!("x: {} y: {}", self.loc.x, self.loc.y));
 
+        egui::CollapsingHeader::new("attributes")
+            .id_source(("attributes", self.i))
+            .show(ui, |ui| {
+                for attr in ATTRIBUTES {
+                    let mut value = attributes.get(attr);
+                    if attribute_ui(ui, attr, &mut value) {
+                        let ids = std::iter::once(self.entity)
+                            .chain(self.counterparts.iter().map(|(id, _)| *id));
+                        self.events
+                            .send_batch(ids.map(|id| PlanetEvent::SetAttribute {
+                                id,
+                                key: attr.key,
+                                value: value.clone(),
+                            }));
+                    }
+                }
+            });
+
         let pn = *player;
+        let (counterparts, symmetry, players) = (self.counterparts, self.symmetry, self.players);
         ui.add(Collapsable::<_, _, &mut EventWriter<PlanetEvent>>::closed(
             |ui: &mut egui::Ui, open: &mut bool, _: &mut &mut EventWriter<PlanetEvent>| {
-                let resp = color_option(ui, COLORS[pn.0], Vec2::splat(32.), false);
+                let resp = color_option(ui, players.color32(pn), Vec2::splat(32.), false)
+                    .on_hover_text(players.name(pn));
 
                 if resp.clicked() {
                     *open = !*open;
@@ -213,14 +263,16 @@ impl<'a, 'w, 's> Widget for PlanetWidget<'a, 'w, 's> {
             |ui: &mut egui::Ui, open: &mut bool, pe: &mut &mut EventWriter<PlanetEvent>| {
                 ui.horizontal_wrapped(move |ui| {
                     let size = Vec2::splat(32.0);
-                    for (i, color) in COLORS.into_iter().enumerate() {
-                        if color_option(ui, color, size, player.0 == i).clicked() {
-                            pe.send(PlanetEvent::SetPlayer {
-                                id: self.entity,
-                                player: Player(i),
-                            });
-                            *open = false;
-                        }
+                    if let Some(new) = player_options(ui, players, Some(*player), size) {
+                        pe.send(PlanetEvent::SetPlayer {
+                            id: self.entity,
+                            player: new,
+                        });
+                        pe.send_batch(counterparts.iter().map(|(id, k)| PlanetEvent::SetPlayer {
+                            id: *id,
+                            player: Player(symmetry.permute(new.0, *k)),
+                        }));
+                        *open = false;
                     }
                 })
                 .response
@@ -231,6 +283,79 @@ impl<'a, 'w, 's> Widget for PlanetWidget<'a, 'w, 's> {
     }
 }
 
+/// Edits `value` as `attr` declares it, returns whether it changed.
+fn attribute_ui(ui: &mut Ui, attr: &Attribute, value: &mut AttributeValue) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(attr.label);
+        match (attr.kind, value) {
+            (AttributeKind::Int { min, max }, AttributeValue::Int(v)) => ui
+                .add(egui::DragValue::new(v).clamp_range(min..=max))
+                .changed(),
+            (AttributeKind::Float { min, max }, AttributeValue::Float(v)) => ui
+                .add(egui::DragValue::new(v).speed(0.05).clamp_range(min..=max))
+                .changed(),
+            (AttributeKind::Tags, AttributeValue::Text(v)) => ui
+                .text_edit_singleline(v)
+                .on_hover_text("Separate tags with commas")
+                .changed(),
+            (_, AttributeValue::Text(v)) => ui.text_edit_singleline(v).changed(),
+            // `Attributes::get` only hands out values of the right type
+            _ => false,
+        }
+    })
+    .inner
+}
+
+/// A color option for every player and neutral, returns the one that was clicked.
+fn player_options(
+    ui: &mut Ui,
+    players: &Players,
+    active: Option<Player>,
+    size: Vec2,
+) -> Option<Player> {
+    let mut clicked = None;
+    for (player, info) in players.iter() {
+        let color = players.color32(player);
+        if color_option(ui, color, size, active == Some(player))
+            .on_hover_text(&info.name)
+            .clicked()
+        {
+            clicked = Some(player);
+        }
+    }
+    clicked
+}
+
+/// Names, colors and teams of the players. Players are only removed from the end, and not
+/// while they own planets.
+fn players_ui(ui: &mut Ui, players: &mut Players, owned: &HashSet<Player>) {
+    egui::Grid::new("players").num_columns(3).show(ui, |ui| {
+        for (i, info) in players.players.iter_mut().enumerate() {
+            egui::color_picker::color_edit_button_srgb(ui, &mut info.color);
+            ui.add(egui::TextEdit::singleline(&mut info.name).desired_width(100.));
+            if i == 0 {
+                ui.label("");
+            } else {
+                ui.add(egui::DragValue::new(&mut info.team).prefix("team "));
+            }
+            ui.end_row();
+        }
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Add player").clicked() {
+            players.add();
+        }
+        let last = Player(players.count());
+        ui.add_enabled_ui(!last.is_neutral() && !owned.contains(&last), |ui| {
+            if ui.button("Remove last").clicked() {
+                players.players.pop();
+            }
+        })
+        .response
+        .on_disabled_hover_text("The last player still owns planets.");
+    });
+}
+
 fn color_option(ui: &mut Ui, color: Color32, size: Vec2, active: bool) -> Response {
     let (response, painter) = ui.allocate_painter(size, Sense::hover().union(Sense::click()));
 
@@ -249,18 +374,371 @@ fn color_option(ui: &mut Ui, color: Color32, size: Vec2, active: bool) -> Respon
     response
 }
 
-struct SceneChannel(SyncSender<SceneEvent>, Receiver<SceneEvent>);
-impl Default for SceneChannel {
+/// Controls for the parts of `params` that `ty` uses, returns whether anything changed.
+fn grid_params_ui(
+    ui: &mut Ui,
+    ty: MapType,
+    params: &mut GridParams,
+    snap_spacing: &mut f32,
+) -> bool {
+    let mut changed = false;
+    if ty != MapType::Free {
+        ui.label("size");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut params.size)
+                    .speed(0.01)
+                    .clamp_range(0.2..=5.0),
+            )
+            .changed();
+    }
+
+    match ty {
+        MapType::Squares => {
+            ui.label("aspect");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut params.square_aspect)
+                        .speed(0.01)
+                        .clamp_range(0.2..=5.0),
+                )
+                .changed();
+        }
+        MapType::Triangles => {
+            let mut columns = params.triangle_orientation == TriangleOrientation::Columns;
+            if ui.checkbox(&mut columns, "columns").changed() {
+                params.triangle_orientation = if columns {
+                    TriangleOrientation::Columns
+                } else {
+                    TriangleOrientation::Rows
+                };
+                changed = true;
+            }
+        }
+        MapType::Hexagons => {
+            let mut pointy = params.hex_orientation == HexOrientation::Pointy;
+            if ui.checkbox(&mut pointy, "pointy").changed() {
+                params.hex_orientation = if pointy {
+                    HexOrientation::Pointy
+                } else {
+                    HexOrientation::Flat
+                };
+                changed = true;
+            }
+        }
+        MapType::Octagons => {
+            ui.label("cut");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut params.octagon_cut)
+                        .speed(0.005)
+                        .clamp_range(OctagonGrid::CUTS),
+                )
+                .changed();
+        }
+        MapType::Free => {
+            if let Some(spacing) = params.snap {
+                *snap_spacing = spacing;
+            }
+            let mut snap = params.snap.is_some();
+            let toggled = ui.checkbox(&mut snap, "snap").changed();
+            let moved = ui
+                .add_enabled(
+                    snap,
+                    egui::DragValue::new(snap_spacing)
+                        .speed(0.05)
+                        .clamp_range(0.1..=10.0),
+                )
+                .changed();
+            if toggled || moved {
+                params.snap = snap.then_some(*snap_spacing);
+                changed = true;
+            }
+        }
+        MapType::Polar => {
+            ui.label("sectors");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut params.sectors)
+                        .speed(0.1)
+                        .clamp_range(PolarGrid::MIN_SECTORS..=24),
+                )
+                .changed();
+        }
+        MapType::Rhombille | MapType::Cairo | MapType::SnubSquare => {}
+    }
+
+    changed
+}
+
+/// Kinds of symmetry offered, rotational symmetry gets its number of copies next to it.
+const SYMMETRY_KINDS: [(SymmetryKind, &str); 5] = [
+    (SymmetryKind::None, "none"),
+    (SymmetryKind::Horizontal, "mirror left / right"),
+    (SymmetryKind::Vertical, "mirror top / bottom"),
+    (SymmetryKind::Point, "point"),
+    (SymmetryKind::Rotational(3), "rotational"),
+];
+
+/// Picks one of `SYMMETRY_KINDS`, returns whether it changed.
+fn symmetry_kind_ui(ui: &mut Ui, label: &str, kind: &mut SymmetryKind) -> bool {
+    let same =
+        |a: &SymmetryKind, b: &SymmetryKind| std::mem::discriminant(a) == std::mem::discriminant(b);
+    let mut changed = false;
+    let selected = SYMMETRY_KINDS
+        .iter()
+        .find(|(k, _)| same(k, kind))
+        .map_or("none", |(_, name)| name);
+    egui::ComboBox::from_label(label)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (option, name) in SYMMETRY_KINDS {
+                let current = same(&option, kind);
+                if ui.selectable_label(current, name).clicked() && !current {
+                    *kind = option;
+                    changed = true;
+                }
+            }
+        });
+    changed
+}
+
+/// Controls for the placement symmetry, returns whether anything changed.
+fn symmetry_ui(ui: &mut Ui, symmetry: &mut Symmetry, players: usize) -> bool {
+    let mut changed = symmetry_kind_ui(ui, "symmetry", &mut symmetry.kind);
+
+    if let SymmetryKind::Rotational(n) = &mut symmetry.kind {
+        // every copy needs its own player color
+        changed |= ui
+            .add(egui::DragValue::new(n).clamp_range(2..=players.max(2)))
+            .changed();
+    }
+
+    if symmetry.kind != SymmetryKind::None {
+        ui.label("center");
+        changed |= ui
+            .add(egui::DragValue::new(&mut symmetry.center.x).speed(0.1))
+            .changed();
+        changed |= ui
+            .add(egui::DragValue::new(&mut symmetry.center.y).speed(0.1))
+            .changed();
+    }
+
+    changed
+}
+
+/// Last snapping distance, kept around while snapping is turned off.
+struct SnapSpacing(f32);
+impl Default for SnapSpacing {
     fn default() -> Self {
-        let (s, r) = sync_channel(5);
-        Self(s, r)
+        Self(0.5)
     }
 }
 
-struct PWUrl(String);
-impl Default for PWUrl {
+/// Values of the selection section, applied to all selected planets at once.
+struct BulkEdit {
+    ship_count: usize,
+    pattern: String,
+}
+impl Default for BulkEdit {
     fn default() -> Self {
-        Self(String::from("https://planetwars.dev/api/maps"))
+        Self {
+            ship_count: 10,
+            pattern: String::from("{name}"),
+        }
+    }
+}
+
+/// Selection wide actions, returns the events they send.
+fn selection_ui(
+    ui: &mut Ui,
+    planets: &[(&PlanetData, Entity, bool)],
+    players: &Players,
+    bulk: &mut BulkEdit,
+) -> Vec<PlanetEvent> {
+    let mut events = Vec::new();
+    let select = |events: &mut Vec<PlanetEvent>, pick: &dyn Fn(&PlanetData) -> bool| {
+        events.extend(
+            planets
+                .iter()
+                .map(|(data, id, _)| PlanetEvent::SetSelected {
+                    id: *id,
+                    selected: pick(data),
+                }),
+        );
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("select");
+        if ui.button("all").clicked() {
+            select(&mut events, &|_| true);
+        }
+        if ui.button("none").clicked() {
+            select(&mut events, &|_| false);
+        }
+    });
+    ui.horizontal_wrapped(|ui| {
+        ui.label("select player");
+        if let Some(player) = player_options(ui, players, None, Vec2::splat(16.)) {
+            select(&mut events, &|data| data.player == player);
+        }
+    });
+
+    let selected: Vec<_> = planets.iter().filter(|(_, _, s)| *s).collect();
+    ui.add_enabled_ui(!selected.is_empty(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("owner");
+            if let Some(player) = player_options(ui, players, None, Vec2::splat(16.)) {
+                events.extend(
+                    selected
+                        .iter()
+                        .map(|(_, id, _)| PlanetEvent::SetPlayer { id: *id, player }),
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("ship count");
+            ui.add(egui::DragValue::new(&mut bulk.ship_count));
+            if ui.button("set").clicked() {
+                events.extend(selected.iter().map(|(_, id, _)| PlanetEvent::SetShipCount {
+                    id: *id,
+                    amount: bulk.ship_count,
+                }));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("rename");
+            ui.text_edit_singleline(&mut bulk.pattern).on_hover_text(
+                "{n} counts up from 1, {name} is the old name and {player} the owner",
+            );
+            if ui.button("set").clicked() {
+                events.extend(selected.iter().enumerate().map(|(i, (data, id, _))| {
+                    PlanetEvent::SetName {
+                        id: *id,
+                        name: rename(&bulk.pattern, i + 1, data),
+                    }
+                }));
+            }
+        });
+        if ui
+            .button(format!("Delete {} planets", selected.len()))
+            .clicked()
+        {
+            events.extend(
+                selected
+                    .iter()
+                    .map(|(_, id, _)| PlanetEvent::Delete { id: *id }),
+            );
+        }
+    });
+
+    events
+}
+
+/// Settings of the generator section, edited over several frames.
+#[derive(Default)]
+struct Generator {
+    settings: GeneratorSettings,
+    error: Option<GenerateError>,
+}
+
+/// Edits a range of ship counts, keeping the start below the end.
+fn ship_range_ui(ui: &mut Ui, label: &str, range: &mut RangeInclusive<usize>) {
+    let (mut start, mut end) = range.clone().into_inner();
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(egui::DragValue::new(&mut start).clamp_range(0..=10000));
+        ui.label("to");
+        ui.add(egui::DragValue::new(&mut end).clamp_range(start..=10000));
+    });
+    *range = start..=end.max(start);
+}
+
+fn generator_ui(ui: &mut Ui, generator: &mut Generator, players: usize) -> bool {
+    let settings = &mut generator.settings;
+    ui.horizontal(|ui| {
+        ui.label("seed");
+        ui.add(egui::DragValue::new(&mut settings.seed));
+        if ui.button("random").clicked() {
+            settings.seed = rand::thread_rng().gen();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("players");
+        ui.add(egui::DragValue::new(&mut settings.players).clamp_range(1..=players.max(1)));
+        ui.label("planets");
+        ui.add(egui::DragValue::new(&mut settings.planets).clamp_range(1..=200));
+    });
+    symmetry_kind_ui(ui, "generator symmetry", &mut settings.symmetry);
+    ui.horizontal(|ui| {
+        ui.label("spacing");
+        ui.add(
+            egui::DragValue::new(&mut settings.min_spacing)
+                .speed(0.05)
+                .clamp_range(0.5..=20.0),
+        );
+        ui.label("to");
+        ui.add(
+            egui::DragValue::new(&mut settings.max_spacing)
+                .speed(0.05)
+                .clamp_range(settings.min_spacing..=50.0),
+        );
+    });
+    ship_range_ui(ui, "home ships", &mut settings.home_ships);
+    ship_range_ui(ui, "neutral ships", &mut settings.neutral_ships);
+
+    if let Some(e) = &generator.error {
+        ui.label(RichText::new(e.to_string()).color(Color32::RED));
+    }
+    ui.button("Generate")
+        .on_hover_text("Replaces all planets.")
+        .clicked()
+}
+
+struct AnalysisPanel {
+    open: bool,
+    /// Turns to look ahead for neutral ships.
+    horizon: u32,
+}
+impl Default for AnalysisPanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            horizon: 10,
+        }
+    }
+}
+
+/// What the side panel needs to export or upload a map.
+struct ExportForm {
+    girth_buf: String,
+    /// Longest expedition in turns, `None` while `girth_buf` is not a number.
+    girth: Option<f32>,
+    url: String,
+    attributes: bool,
+    format: &'static dyn ExportFormat,
+    /// See [`ExportOptions::labels`].
+    labels: bool,
+    /// See [`ExportOptions::distances`].
+    distances: bool,
+    /// Map type that imported planets are put on.
+    import_ty: MapType,
+    /// Cells between the two closest imported planets.
+    import_spacing: f32,
+}
+impl Default for ExportForm {
+    fn default() -> Self {
+        Self {
+            girth_buf: String::new(),
+            girth: None,
+            url: String::from("https://planetwars.dev/api/maps"),
+            attributes: false,
+            format: FORMATS[0],
+            labels: true,
+            distances: false,
+            import_ty: MapType::Hexagons,
+            import_spacing: 2.,
+        }
     }
 }
 
@@ -271,15 +749,33 @@ fn ui_editor(
     mut hovering_ui: ResMut<HoveringUI>,
     mut planet_events: EventWriter<PlanetEvent>,
     mut scene_events: EventWriter<SceneEvent>,
+    mut report: ResMut<ConversionReport>,
+    config: Res<MapConfig>,
+    mut map_events: EventWriter<MapEvent>,
+
+    validation: Res<Validation>,
 
-    mut size_buf: Local<String>,
-    mut url_buf: Local<PWUrl>,
-    mut map_name: Local<String>,
-    mut scale: Local<f32>,
-    mut enabled: Local<bool>,
+    mut form: Local<ExportForm>,
     mut help_closed: Local<bool>,
+    mut analysis: Local<AnalysisPanel>,
+    mut generator: Local<Generator>,
+    mut bulk: Local<BulkEdit>,
+    mut players: ResMut<Players>,
+    mut map_name: ResMut<MapName>,
 ) {
     hovering_ui.0 = false;
+
+    let grid = config.grid();
+    let by_location: HashMap<_, _> = query.iter().map(|(l, _, e, _)| (*l, e)).collect();
+    let counterparts = |loc: &Location| {
+        config
+            .symmetry
+            .counterparts(grid.as_ref(), *loc)
+            .into_iter()
+            .filter_map(|(other, k)| Some((*by_location.get(&other)?, k)))
+            .collect()
+    };
+
     let resp = egui::SidePanel::right("right_panel")
         .min_width(250.)
         .resizable(true)
@@ -295,40 +791,189 @@ fn ui_editor(
                 }
             });
 
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Import")
+                    .on_hover_text("Reads a map exported for planetwars.")
+                    .clicked()
+                {
+                    scene_events.send(SceneEvent::Import {
+                        ty: form.import_ty,
+                        spacing: form.import_spacing,
+                    });
+                }
+                egui::ComboBox::from_id_source("import type")
+                    .selected_text(format!("{:?}", form.import_ty))
+                    .show_ui(ui, |ui| {
+                        for ty in MapType::ALL {
+                            ui.selectable_value(&mut form.import_ty, ty, format!("{:?}", ty));
+                        }
+                    });
+                ui.add(
+                    egui::DragValue::new(&mut form.import_spacing)
+                        .speed(0.1)
+                        .clamp_range(0.5..=10.)
+                        .prefix("spacing "),
+                )
+                .on_hover_text("Cells between the two closest planets.");
+            });
+
             ui.horizontal(|ui| {
                 ui.label("Longest expedition in turns: ");
-                if ui.text_edit_singleline(size_buf.deref_mut()).changed() {
-                    if let Ok(ns) = size_buf.parse() {
-                        *scale = ns;
-                        *enabled = true;
-                    } else {
-                        *enabled = false;
-                    }
+                if ui.text_edit_singleline(&mut form.girth_buf).changed() {
+                    form.girth = form.girth_buf.parse().ok();
                 };
             });
 
-                ui.label("Planetwars upload url: ");
-                ui.text_edit_singleline(&mut url_buf.deref_mut().0);
-                ui.label("Map name: ");
-                ui.text_edit_singleline(map_name.deref_mut());
+            ui.label("Planetwars upload url: ");
+            ui.text_edit_singleline(&mut form.url);
+            ui.label("Map name: ");
+            ui.text_edit_singleline(&mut map_name.0);
+            ui.checkbox(&mut form.attributes, "Export planet attributes")
+                .on_hover_text("Growth rate, size and the other attributes, for game variants.");
+
+            if form.format.id() == Svg.id() {
+                ui.checkbox(&mut form.labels, "Draw names and ship counts");
+                ui.checkbox(&mut form.distances, "Draw turns between close planets");
+            }
 
-            ui.add_enabled_ui(*enabled && map_name.len() > 0, |ui| {
+            let ready = form.girth.is_some() && !map_name.0.is_empty();
+            ui.add_enabled_ui(ready && !validation.blocks_export(), |ui| {
                 ui.horizontal(|ui| {
+                    let options = ExportOptions {
+                        girth: form.girth.unwrap_or_default(),
+                        name: map_name.0.clone(),
+                        attributes: form.attributes,
+                        labels: form.labels,
+                        distances: form.distances,
+                    };
+                    if ui.button("Export").clicked() {
+                        scene_events.send(SceneEvent::Export {
+                            options: options.clone(),
+                            format: form.format,
+                        });
+                    }
+                    egui::ComboBox::from_id_source("export format")
+                        .selected_text(form.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in FORMATS {
+                                let current = format.id() == form.format.id();
+                                if ui.selectable_label(current, format.label()).clicked() {
+                                    form.format = *format;
+                                }
+                            }
+                        });
 
-                if ui.button("Export").clicked() {
-                    scene_events.send(SceneEvent::Export{girth: *scale, name: map_name.to_string()});
-                }
+                    if ui
+                        .button("Upload")
+                        .on_hover_text("Uploads the planetwars JSON, whatever the format.")
+                        .clicked()
+                    {
+                        scene_events.send(SceneEvent::Upload {
+                            options,
+                            url: form.url.clone(),
+                        });
+                    }
+                });
+            })
+            .response
+            .on_disabled_hover_text("Fix the problems below first.");
 
-                if ui.button("Upload").clicked() {
-                    scene_events.send(SceneEvent::Upload{girth: *scale, url: url_buf.0.clone(), name: map_name.to_string()});
+            if !validation.issues.is_empty() {
+                ui.add_space(8.);
+                ui.label(RichText::new("Problems").color(ZEUS));
+                for issue in validation.issues.iter() {
+                    let color = match issue.severity() {
+                        Severity::Error => Color32::RED,
+                        Severity::Warning => Color32::YELLOW,
+                    };
+                    let label = ui.add(
+                        egui::Label::new(RichText::new(&issue.message).color(color))
+                            .sense(Sense::click()),
+                    );
+                    if label.clicked() {
+                        if let Some(loc) = issue.locations.first() {
+                            map_events.send(MapEvent::Focus(*loc));
+                        }
+                        planet_events.send_batch(issue.locations.iter().filter_map(|l| {
+                            Some(PlanetEvent::SetSelected {
+                                id: *by_location.get(l)?,
+                                selected: true,
+                            })
+                        }));
+                    }
+                    if !issue.locations.is_empty() {
+                        label.on_hover_text("Click to show");
+                    }
                 }
+            }
+
+            ui.checkbox(&mut analysis.open, "Show fairness analysis");
+
+            let planets: Vec<_> = query.iter().map(|(_, d, e, s)| (d, e, s.0)).collect();
+            let count = planets.iter().filter(|(_, _, s)| *s).count();
+            egui::CollapsingHeader::new(format!("Selection ({})", count))
+                .id_source("selection")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let events = selection_ui(ui, &planets, &players, &mut bulk);
+                    planet_events.send_batch(events.into_iter());
                 });
+
+            egui::CollapsingHeader::new("Players").show(ui, |ui| {
+                let owned: HashSet<_> = query.iter().map(|(_, d, _, _)| d.player).collect();
+                // only touch the resource on edits, a change repaints every planet
+                let mut edited = players.clone();
+                players_ui(ui, &mut edited, &owned);
+                if edited != *players {
+                    *players = edited;
+                }
+            });
+
+            egui::CollapsingHeader::new("Generate map").show(ui, |ui| {
+                if generator_ui(ui, &mut generator, players.count()) {
+                    match generate(&generator.settings, grid.as_ref()) {
+                        Ok(events) => {
+                            generator.error = None;
+                            planet_events.send_batch(
+                                query.iter().map(|(_, _, id, _)| PlanetEvent::Delete { id }),
+                            );
+                            planet_events.send_batch(events.into_iter());
+                            // keep editing the map with the symmetry it was made with
+                            map_events.send(MapEvent::SetSymmetry(generator.settings.symmetry()));
+                        }
+                        Err(e) => generator.error = Some(e),
+                    }
+                }
             });
 
             ui.add_space(8.);
             ui.separator();
             ui.add_space(8.);
 
+            if !report.collisions.is_empty() {
+                ui.label(RichText::new("Moved planets").color(ZEUS));
+                for c in report.collisions.iter() {
+                    if c.placed == c.wanted {
+                        ui.label(format!(
+                            "{} shares a cell at x: {} y: {}",
+                            c.name, c.wanted.x, c.wanted.y
+                        ));
+                    } else {
+                        ui.label(format!(
+                            "{} moved to x: {} y: {}, x: {} y: {} was taken",
+                            c.name, c.placed.x, c.placed.y, c.wanted.x, c.wanted.y
+                        ));
+                    }
+                }
+                if ui.button("Dismiss").clicked() {
+                    report.collisions.clear();
+                }
+                ui.add_space(8.);
+                ui.separator();
+                ui.add_space(8.);
+            }
+
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.label(RichText::new("Selected").color(ZEUS));
                 for (i, (l, player, e, s)) in query.iter().enumerate() {
@@ -338,6 +983,9 @@ fn ui_editor(
                             data: player,
                             loc: l,
                             entity: e,
+                            counterparts: counterparts(l),
+                            symmetry: &config.symmetry,
+                            players: &players,
                             events: &mut planet_events,
                         });
                         ui.separator();
@@ -353,6 +1001,9 @@ fn ui_editor(
                             data: player,
                             loc: l,
                             entity: e,
+                            counterparts: counterparts(l),
+                            symmetry: &config.symmetry,
+                            players: &players,
                             events: &mut planet_events,
                         });
                         ui.separator();
@@ -369,17 +1020,29 @@ fn ui_editor(
                     ui.label("Right click to delete a planet.");
                     ui.label("Click colored squares to change planet owner.");
                     ui.label("Click different shapes to change the map layout.");
+                    ui.label("Pick a symmetry to place and delete planets together with their mirrored copies.");
+                    ui.label("Ctrl + Z undoes the last change, Ctrl + Shift + Z redoes it.");
+                    ui.label("Drag from an empty spot to select all planets in a box.");
+                    ui.label("Hold shift to add to the selection, ctrl to remove from it, Esc clears it.");
+                    ui.label("Drag a planet to move it, dragging a selected planet moves the whole selection.");
+                    ui.label("Ctrl + C, X and V copy, cut and paste the selection, click to place a paste.");
                 ui.separator();
                     ui.heading("Editing");
                     ui.label("Right, change the name of the planet and ship count.");
                     ui.label("You can also change to owner of a planet on the right side.");
+                    ui.label("'Players' on the right sets the names, colors and teams of the players.");
+                    ui.label("Open 'attributes' under a planet to set its growth rate, size, production and tags.");
                 ui.separator();
                     ui.heading("Exporting");
                     ui.label("'Save' lets you save the editor's state to resume later.");
                     ui.label("'Load' lets you resume the eidtor's state.");
+                    ui.label("'Import' reads an exported map onto the chosen map type, with the closest planets 'spacing' cells apart.");
                     ui.label("'Export' lets you export your creation to a valid planetwars map.");
+                    ui.label("The list next to it picks the format, like CSV, JSON with metadata or an SVG drawing of the map.");
+                    ui.label("'Upload' sends the export to the upload url, a window tells how it went.");
                     ui.label("Longest expedition is a field that changes the scale of the map");
                     ui.label("the number entered is the total number of turns between the furthest planets on the map.");
+                    ui.label("Problems in red block exporting, click one to show the planets involved.");
                 ui.separator();
                     if ui.button("Close help").clicked() {
                         *help_closed = true;
@@ -390,6 +1053,74 @@ fn ui_editor(
         })
         .response;
     hovering_ui.0 = hovering_ui.0 || resp.hovered();
+
+    if analysis.open {
+        let resp = egui::SidePanel::right("analysis_panel")
+            .min_width(200.)
+            .resizable(true)
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.add_space(8.);
+                ui.heading("Fairness");
+                ui.horizontal(|ui| {
+                    ui.label("Horizon in turns: ");
+                    ui.add(egui::DragValue::new(&mut analysis.horizon).clamp_range(1..=500));
+                });
+                ui.separator();
+
+                let girth = match form.girth {
+                    Some(girth) => girth,
+                    None => {
+                        ui.label("Enter the longest expedition to measure in turns.");
+                        return;
+                    }
+                };
+                let planets: Vec<_> = query
+                    .iter()
+                    .map(|(l, data, _, _)| (grid.center(*l), data))
+                    .collect();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    match analyze(&planets, girth, analysis.horizon) {
+                        Some(report) => analysis_ui(ui, &report, &players),
+                        None => {
+                            ui.label("Planets need some distance between them to measure turns.");
+                        }
+                    }
+                });
+            })
+            .response;
+        hovering_ui.0 = hovering_ui.0 || resp.hovered();
+    }
+}
+
+fn analysis_ui(ui: &mut Ui, analysis: &Analysis, players: &Players) {
+    match analysis.fairness {
+        Some(f) => ui.label(format!("Score: {:.0}%", f * 100.)),
+        None => ui.label("Needs at least two players."),
+    };
+
+    for report in analysis.players.iter() {
+        ui.separator();
+        ui.label(RichText::new(players.name(report.player)).color(players.color32(report.player)));
+        ui.label(format!(
+            "{} planets with {} ships",
+            report.planets, report.ships
+        ));
+        ui.label(format!(
+            "{} neutral ships within {} turns",
+            report.reachable_ships, analysis.horizon
+        ));
+        ui.label(format!("{:.1} ships claimed first", report.claimed_ships));
+        if let Some(d) = report.nearest_enemy {
+            ui.label(format!("Nearest enemy in {} turns", d));
+        }
+        egui::CollapsingHeader::new("Neutral planets")
+            .id_source(("analysis", report.player.0))
+            .show(ui, |ui| {
+                for (name, turns) in report.neutral_turns.iter() {
+                    ui.label(format!("{}: {} turns", name, turns));
+                }
+            });
+    }
 }
 
 struct IconButton {
@@ -421,15 +1152,133 @@ impl Widget for IconButton {
     }
 }
 
+/// Tells about saves that could not be loaded or were upgraded, and about imports, until
+/// dismissed.
+fn load_report_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut report: ResMut<LoadReport>,
+    mut hovering_ui: ResMut<HoveringUI>,
+) {
+    let text = match (&report.error, report.upgraded_from, &report.import) {
+        (Some(e), _, _) => RichText::new(e.to_string()).color(Color32::RED),
+        (None, Some(v), _) => RichText::new(format!(
+            "The save was made with version {} and has been upgraded, saving it writes version {}.",
+            v, VERSION
+        )),
+        (None, None, Some(Err(e))) => RichText::new(e.to_string()).color(Color32::RED),
+        (None, None, Some(Ok(import))) if import.moved.is_empty() => {
+            RichText::new(format!("Imported {} planets.", import.planets))
+        }
+        (None, None, Some(Ok(import))) => RichText::new(format!(
+            "Imported {} planets, {} did not fit on their own cell and moved to the nearest free one: {}",
+            import.planets,
+            import.moved.len(),
+            import.moved.join(", ")
+        ))
+        .color(Color32::YELLOW),
+        (None, None, None) => return,
+    };
+
+    let mut open = true;
+    let resp = egui::Window::new("Load")
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(text);
+            if ui.button("Ok").clicked() {
+                open = false;
+            }
+        });
+    if let Some(resp) = resp {
+        hovering_ui.0 = hovering_ui.0 || resp.response.hovered();
+    }
+    if !open {
+        *report = LoadReport::default();
+    }
+}
+
+/// Where the last upload is at.
+#[derive(Default)]
+enum UploadStatus {
+    #[default]
+    Idle,
+    Sending,
+    Finished(UploadResult),
+}
+
+/// Shows that an upload is going on and how it went, until dismissed.
+fn upload_report_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut scene_events: EventReader<SceneEvent>,
+    mut upload_events: EventReader<UploadEvent>,
+    mut status: Local<UploadStatus>,
+    mut hovering_ui: ResMut<HoveringUI>,
+) {
+    if scene_events
+        .iter()
+        .any(|e| matches!(e, SceneEvent::Upload { .. }))
+    {
+        *status = UploadStatus::Sending;
+    }
+    if let Some(UploadEvent(result)) = upload_events.iter().last() {
+        *status = UploadStatus::Finished(result.clone());
+    }
+
+    let text = match &*status {
+        UploadStatus::Idle => return,
+        UploadStatus::Sending => RichText::new("Uploading..."),
+        UploadStatus::Finished(result @ UploadResult::Done(_)) => RichText::new(result.to_string()),
+        UploadStatus::Finished(result) => RichText::new(result.to_string()).color(Color32::RED),
+    };
+
+    let mut open = true;
+    let resp = egui::Window::new("Upload")
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(text);
+            if let UploadStatus::Finished(_) = *status {
+                if ui.button("Ok").clicked() {
+                    open = false;
+                }
+            }
+        });
+    if let Some(resp) = resp {
+        hovering_ui.0 = hovering_ui.0 || resp.response.hovered();
+    }
+    if !open {
+        *status = UploadStatus::Idle;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_system(
     mut egui_context: ResMut<EguiContext>,
     fps: Res<FPS>,
-    config: Res<MapConfig>,
+    mut config: ResMut<MapConfig>,
     mut player: ResMut<Player>,
     mut hovering_ui: ResMut<HoveringUI>,
     icons: Res<Icons>,
     mut writer: EventWriter<MapEvent>,
+    mut snap_spacing: Local<SnapSpacing>,
+    history: Res<History>,
+    mut history_events: EventWriter<HistoryEvent>,
+    keys: Res<Input<KeyCode>>,
+    players: Res<Players>,
 ) {
+    let ctx = egui_context.ctx_mut();
+    // text fields have their own undo
+    if !ctx.wants_keyboard_input()
+        && keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+        && keys.just_pressed(KeyCode::Z)
+    {
+        if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+            history_events.send(HistoryEvent::Redo);
+        } else {
+            history_events.send(HistoryEvent::Undo);
+        }
+    }
+
     egui::TopBottomPanel::bottom("bottom_panel")
         // .default_height(70.)
         .show(egui_context.ctx_mut(), |ui| {
@@ -443,60 +1292,65 @@ fn ui_system(
                     ui.label(format!("hovering {}", hovering_ui.0));
                     ui.label(format!("zoom {}", config.zoom));
 
-                    let size = Vec2::splat(32.0);
-                    for (i, color) in COLORS.into_iter().enumerate() {
-                        if color_option(ui, color, size, i == player.0).clicked() {
-                            player.0 = i;
-                        }
-                    }
-
                     ui.separator();
-
                     if ui
-                        .add(IconButton {
-                            id: icons.triangles,
-                            selected: config.ty == MapType::Triangles,
-                        })
+                        .add_enabled(history.can_undo(), egui::Button::new("Undo"))
+                        .on_hover_text("Ctrl + Z")
                         .clicked()
-                        && config.ty != MapType::Triangles
                     {
-                        writer.send(MapEvent::SetType(MapType::Triangles));
+                        history_events.send(HistoryEvent::Undo);
                     }
-
                     if ui
-                        .add(IconButton {
-                            id: icons.squares,
-                            selected: config.ty == MapType::Squares,
-                        })
+                        .add_enabled(history.can_redo(), egui::Button::new("Redo"))
+                        .on_hover_text("Ctrl + Shift + Z")
                         .clicked()
-                        && config.ty != MapType::Squares
                     {
-                        writer.send(MapEvent::SetType(MapType::Squares));
+                        history_events.send(HistoryEvent::Redo);
                     }
+                    ui.separator();
 
-                    if ui
-                        .add(IconButton {
-                            id: icons.hexagons,
-                            selected: config.ty == MapType::Hexagons,
-                        })
-                        .clicked()
-                        && config.ty != MapType::Hexagons
-                    {
-                        writer.send(MapEvent::SetType(MapType::Hexagons));
+                    let size = Vec2::splat(32.0);
+                    if let Some(new) = player_options(ui, &players, Some(*player), size) {
+                        *player = new;
+                    }
+
+                    ui.separator();
+
+                    for (ty, id) in icons.map_types.iter() {
+                        if ui
+                            .add(IconButton {
+                                id: *id,
+                                selected: config.ty == *ty,
+                            })
+                            .clicked()
+                            && config.ty != *ty
+                        {
+                            writer.send(config.switch_to(*ty));
+                        }
                     }
 
+                    let mut keep_positions = config.keep_positions;
                     if ui
-                        .add(IconButton {
-                            id: icons.octagons,
-                            selected: config.ty == MapType::Octagons,
-                        })
-                        .clicked()
-                        && config.ty != MapType::Octagons
+                        .checkbox(&mut keep_positions, "keep positions")
+                        .on_hover_text("Move planets to the closest cell of the new map type instead of keeping their x and y.")
+                        .changed()
                     {
-                        writer.send(MapEvent::SetType(MapType::Octagons));
+                        config.keep_positions = keep_positions;
                     }
 
                     ui.separator();
+
+                    let mut params = config.params;
+                    if grid_params_ui(ui, config.ty, &mut params, &mut snap_spacing.0) {
+                        writer.send(MapEvent::SetParams(params));
+                    }
+
+                    ui.separator();
+
+                    let mut symmetry = config.symmetry;
+                    if symmetry_ui(ui, &mut symmetry, players.count()) {
+                        writer.send(MapEvent::SetSymmetry(symmetry));
+                    }
                 })
             })
         });
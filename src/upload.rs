@@ -0,0 +1,48 @@
+//! Sending an export to the planetwars server.
+//!
+//! The request runs in the background, its result comes back as an [`UploadEvent`].
+
+use std::fmt;
+
+/// How an upload went.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UploadResult {
+    /// The server took the map, with the status it answered.
+    Done(u16),
+    /// The server answered with an error status, and the body of its answer.
+    Refused { status: u16, body: String },
+    /// The server could not be reached.
+    Failed(String),
+}
+
+impl fmt::Display for UploadResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadResult::Done(status) => write!(f, "Uploaded ({})", status),
+            UploadResult::Refused { status, body } if body.is_empty() => {
+                write!(f, "The server refused the map ({})", status)
+            }
+            UploadResult::Refused { status, body } => {
+                write!(f, "The server refused the map ({}): {}", status, body)
+            }
+            UploadResult::Failed(e) => write!(f, "Upload failed: {}", e),
+        }
+    }
+}
+
+/// Sent when an upload is finished.
+pub struct UploadEvent(pub UploadResult);
+
+/// Posts the export `content` to `url`, blocking until the server answers.
+#[cfg(not(target_family = "wasm"))]
+pub fn post(url: &str, content: &str) -> UploadResult {
+    let request = ureq::post(url).set("Content-Type", "application/json");
+    match request.send_string(content) {
+        Ok(response) => UploadResult::Done(response.status()),
+        Err(ureq::Error::Status(status, response)) => UploadResult::Refused {
+            status,
+            body: response.into_string().unwrap_or_default(),
+        },
+        Err(e) => UploadResult::Failed(e.to_string()),
+    }
+}
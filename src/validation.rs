@@ -0,0 +1,240 @@
+//! Checks a map has to pass before it is exported or uploaded.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::analysis::export_scale;
+use crate::geometry::Grid;
+use crate::map_config::MapConfig;
+use crate::planet::{Location, PlanetData, Player, Players};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    /// The map can not be exported.
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    NoPlanets,
+    /// All planets are on the same spot, so the export scale divides by zero.
+    NoSpread,
+    EmptyName,
+    DuplicateName,
+    SharedCell,
+    NoPlayers,
+    SinglePlayer,
+    /// A player number is skipped, like when there are planets of player 1 and 3 but not 2.
+    MissingPlayer,
+    /// Players start with different numbers of ships.
+    UnevenStart,
+    /// The longest expedition is not a positive number of turns.
+    Girth,
+}
+
+impl Rule {
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::MissingPlayer | Rule::UnevenStart => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub rule: Rule,
+    pub message: String,
+    /// The planets at fault, to point them out.
+    pub locations: Vec<Location>,
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        self.rule.severity()
+    }
+}
+
+/// Result of the last validation, kept up to date while editing.
+#[derive(Default)]
+pub struct Validation {
+    pub issues: Vec<Issue>,
+}
+
+impl Validation {
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Error)
+    }
+
+    pub fn blocks_export(&self) -> bool {
+        self.errors().next().is_some()
+    }
+}
+
+pub struct ValidationPlugin;
+impl Plugin for ValidationPlugin {
+    fn build(&self, app: &mut App) {
+        // after the commands of `Update` ran, so removed planets are seen
+        app.init_resource::<Validation>()
+            .add_system_to_stage(CoreStage::PostUpdate, update_validation);
+    }
+}
+
+fn update_validation(
+    planets: Query<(&Location, &PlanetData)>,
+    changed: Query<(), Or<(Changed<Location>, Changed<PlanetData>)>>,
+    removed: RemovedComponents<PlanetData>,
+    config: Res<MapConfig>,
+    players: Res<Players>,
+    mut validation: ResMut<Validation>,
+) {
+    let unchanged = changed.is_empty() && removed.iter().next().is_none();
+    if unchanged && !config.is_changed() && !players.is_changed() {
+        return;
+    }
+    let planets: Vec<_> = planets.iter().map(|(l, d)| (*l, d)).collect();
+    validation.issues = validate(&planets, config.grid().as_ref(), &players, None);
+}
+
+/// Runs every rule over `planets`, errors first. The longest expedition is only checked when
+/// `girth` is given, `players` names the players in the messages.
+pub fn validate(
+    planets: &[(Location, &PlanetData)],
+    grid: &dyn Grid,
+    players: &Players,
+    girth: Option<f32>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut issue = |rule, message: String, locations: Vec<Location>| {
+        issues.push(Issue {
+            rule,
+            message,
+            locations,
+        })
+    };
+
+    if let Some(girth) = girth.filter(|g| !(g.is_finite() && *g > 0.)) {
+        issue(
+            Rule::Girth,
+            format!("The longest expedition can not be {} turns", girth),
+            Vec::new(),
+        );
+    }
+
+    if planets.is_empty() {
+        issue(
+            Rule::NoPlanets,
+            "The map has no planets".to_string(),
+            Vec::new(),
+        );
+        return issues;
+    }
+
+    let points: Vec<_> = planets.iter().map(|(l, _)| grid.center(*l)).collect();
+    if export_scale(1., &points).is_none() {
+        issue(
+            Rule::NoSpread,
+            "Planets need some distance between them to scale the map".to_string(),
+            planets.iter().map(|(l, _)| *l).collect(),
+        );
+    }
+
+    let mut by_name: HashMap<&str, Vec<Location>> = HashMap::new();
+    let mut by_cell: HashMap<Location, Vec<&str>> = HashMap::new();
+    for (loc, data) in planets {
+        by_name.entry(data.name.trim()).or_default().push(*loc);
+        by_cell.entry(*loc).or_default().push(&data.name);
+    }
+
+    if let Some(locations) = by_name.remove("") {
+        issue(
+            Rule::EmptyName,
+            format!("{} planet(s) without a name", locations.len()),
+            locations,
+        );
+    }
+    let mut names: Vec<_> = by_name.into_iter().filter(|(_, l)| l.len() > 1).collect();
+    names.sort_by_key(|(name, _)| *name);
+    for (name, locations) in names {
+        issue(
+            Rule::DuplicateName,
+            format!("{} planets are called {}", locations.len(), name),
+            locations,
+        );
+    }
+
+    let mut cells: Vec<_> = by_cell.into_iter().filter(|(_, n)| n.len() > 1).collect();
+    cells.sort_by_key(|(l, _)| (l.x, l.y));
+    for (loc, names) in cells {
+        issue(
+            Rule::SharedCell,
+            format!(
+                "{} share the cell at x: {} y: {}",
+                names.join(", "),
+                loc.x,
+                loc.y
+            ),
+            vec![loc],
+        );
+    }
+
+    let name = |player| players.name(Player(player));
+    let mut players: Vec<_> = planets
+        .iter()
+        .map(|(_, d)| d.player.0)
+        .filter(|p| *p != 0)
+        .collect();
+    players.sort_unstable();
+    players.dedup();
+    match players[..] {
+        [] => issue(
+            Rule::NoPlayers,
+            "No planet belongs to a player".to_string(),
+            Vec::new(),
+        ),
+        [player] => issue(
+            Rule::SinglePlayer,
+            format!("Only {} has planets", name(player)),
+            planets
+                .iter()
+                .filter(|(_, d)| d.player.0 == player)
+                .map(|(l, _)| *l)
+                .collect(),
+        ),
+        _ => {
+            let owned = |player| planets.iter().filter(move |(_, d)| d.player.0 == player);
+            let last = *players.last().unwrap();
+            for player in (1..last).filter(|p| !players.contains(p)) {
+                issue(
+                    Rule::MissingPlayer,
+                    format!("{} has no planets", name(player)),
+                    Vec::new(),
+                );
+            }
+
+            let ships: Vec<usize> = players
+                .iter()
+                .map(|p| owned(*p).map(|(_, d)| d.ship_count).sum())
+                .collect();
+            if ships.iter().any(|s| *s != ships[0]) {
+                let counts: Vec<_> = players
+                    .iter()
+                    .zip(&ships)
+                    .map(|(p, s)| format!("{}: {}", name(*p), s))
+                    .collect();
+                issue(
+                    Rule::UnevenStart,
+                    format!("Players start with different ships, {}", counts.join(", ")),
+                    Vec::new(),
+                );
+            }
+        }
+    }
+
+    issues.sort_by_key(|i| std::cmp::Reverse(i.severity()));
+    issues
+}
@@ -0,0 +1,92 @@
+use mapbuilder::analysis::{analyze, export_scale};
+use mapbuilder::geometry::Point;
+use mapbuilder::planet::{PlanetData, Player};
+
+fn planet(name: &str, player: usize, ship_count: usize) -> PlanetData {
+    PlanetData {
+        player: Player(player),
+        name: name.to_string(),
+        ship_count,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn furthest_planets_end_up_girth_apart() {
+    let points = [Point::new(0., 0.), Point::new(3., 4.), Point::new(1., 1.)];
+    assert!((export_scale(20., &points).unwrap() * 5. - 20.).abs() < 1e-4);
+}
+
+#[test]
+fn planets_on_one_spot_have_no_scale() {
+    let points = [Point::new(1., 2.), Point::new(1., 2.)];
+    assert_eq!(export_scale(20., &points), None);
+    assert_eq!(export_scale(20., &points[..1]), None);
+    assert_eq!(export_scale(20., &[]), None);
+
+    let data = [planet("a", 1, 5), planet("b", 2, 5)];
+    let planets: Vec<_> = points.into_iter().zip(data.iter()).collect();
+    assert!(analyze(&planets, 10., 5).is_none());
+}
+
+#[test]
+fn mirrored_map_is_fair() {
+    let data = [
+        planet("a", 1, 100),
+        planet("b", 2, 100),
+        planet("left", 0, 10),
+        planet("right", 0, 10),
+        planet("middle", 0, 30),
+    ];
+    let points = [
+        Point::new(-5., 0.),
+        Point::new(5., 0.),
+        Point::new(-3., 1.),
+        Point::new(3., 1.),
+        Point::new(0., 2.),
+    ];
+    let planets: Vec<_> = points.into_iter().zip(data.iter()).collect();
+    let analysis = analyze(&planets, 10., 3).unwrap();
+
+    assert_eq!(analysis.fairness, Some(1.));
+    assert_eq!(analysis.players.len(), 2);
+    for report in analysis.players.iter() {
+        assert_eq!(report.nearest_enemy, Some(10));
+        // the own side is close, the other side and the middle are not
+        assert_eq!(report.reachable_ships, 10);
+        assert_eq!(report.claimed_ships, 100. + 10. + 15.);
+    }
+    let turns: Vec<_> = analysis.players[0]
+        .neutral_turns
+        .iter()
+        .map(|(_, t)| *t)
+        .collect();
+    assert_eq!(turns, vec![3, 9, 6]);
+}
+
+#[test]
+fn lopsided_map_is_unfair() {
+    let data = [
+        planet("a", 1, 50),
+        planet("b", 2, 50),
+        planet("near a", 0, 50),
+    ];
+    let points = [Point::new(0., 0.), Point::new(10., 0.), Point::new(1., 0.)];
+    let planets: Vec<_> = points.into_iter().zip(data.iter()).collect();
+    let analysis = analyze(&planets, 10., 5).unwrap();
+
+    assert_eq!(analysis.fairness, Some(0.5));
+    assert_eq!(analysis.players[0].reachable_ships, 50);
+    assert_eq!(analysis.players[1].reachable_ships, 0);
+}
+
+#[test]
+fn single_player_has_no_score() {
+    let data = [planet("a", 1, 5), planet("b", 0, 5)];
+    let points = [Point::new(0., 0.), Point::new(2., 0.)];
+    let planets: Vec<_> = points.into_iter().zip(data.iter()).collect();
+    let analysis = analyze(&planets, 10., 5).unwrap();
+
+    assert_eq!(analysis.fairness, None);
+    assert_eq!(analysis.players[0].nearest_enemy, None);
+}
@@ -0,0 +1,60 @@
+use mapbuilder::attributes::{attribute, AttributeValue, Attributes, ATTRIBUTES};
+use mapbuilder::planet::{PlanetData, Player};
+
+#[test]
+fn keys_are_unique() {
+    for (i, a) in ATTRIBUTES.iter().enumerate() {
+        assert!(a.accepts(&a.default), "{} rejects its default", a.key);
+        assert!(ATTRIBUTES[i + 1..].iter().all(|b| b.key != a.key));
+    }
+}
+
+#[test]
+fn defaults_are_not_stored() {
+    let growth = attribute("growth_rate").unwrap();
+    let mut attributes = Attributes::default();
+    assert_eq!(attributes.get(growth), growth.default);
+
+    attributes.set(growth, AttributeValue::Int(4));
+    assert_eq!(attributes.get(growth), AttributeValue::Int(4));
+    attributes.set(growth, growth.default.clone());
+    assert!(attributes.is_empty());
+}
+
+#[test]
+fn values_of_the_wrong_type_read_as_default() {
+    let attributes: Attributes =
+        serde_json::from_str(r#"{ "size": "huge", "production": 5000, "unknown": 1 }"#).unwrap();
+    let size = attribute("size").unwrap();
+    let production = attribute("production").unwrap();
+    assert_eq!(attributes.get(size), size.default);
+    // out of range
+    assert_eq!(attributes.get(production), production.default);
+}
+
+#[test]
+fn saves_only_mention_set_attributes() {
+    let mut data = PlanetData {
+        player: Player(1),
+        name: "vega".to_string(),
+        ship_count: 3,
+        ..Default::default()
+    };
+    let text = serde_json::to_string(&data).unwrap();
+    assert!(!text.contains("attributes"));
+    // older saves have no attributes at all
+    let old: PlanetData = serde_json::from_str(&text).unwrap();
+    assert!(old.attributes.is_empty());
+
+    let tags = attribute("tags").unwrap();
+    data.attributes
+        .set(tags, AttributeValue::Text("home, , rich ".to_string()));
+    let text = serde_json::to_string(&data).unwrap();
+    let back: PlanetData = serde_json::from_str(&text).unwrap();
+    assert_eq!(back.attributes, data.attributes);
+
+    let export = back.attributes.export();
+    assert_eq!(export["tags"], serde_json::json!(["home", "rich"]));
+    assert_eq!(export["growth_rate"], serde_json::json!(1));
+    assert_eq!(export.len(), ATTRIBUTES.len());
+}
@@ -0,0 +1,143 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mapbuilder-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// An empty directory of its own for every test.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mapbuilder-cli-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The v1 fixture with a nameless planet, which can not be exported.
+fn broken_save(dir: &Path) -> PathBuf {
+    let mut save: Value =
+        serde_json::from_str(&fs::read_to_string(fixture("save_v1.json")).unwrap()).unwrap();
+    save["planets"][0]["data"]["name"] = "".into();
+    let path = dir.join("broken.json");
+    fs::write(&path, save.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn convert_prints_the_export() {
+    let save = fixture("save_v1.json");
+    let out = cli(&["convert", save.to_str().unwrap(), "--girth", "20"]);
+    assert!(out.status.success());
+    let export: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(export["name"], "save_v1");
+    assert_eq!(export["planets"].as_array().unwrap().len(), 3);
+    assert!(export["planets"][0].get("growth_rate").is_none());
+
+    let out = cli(&[
+        "convert",
+        save.to_str().unwrap(),
+        "--girth",
+        "20",
+        "--name",
+        "duel",
+        "--attributes",
+    ]);
+    let export: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(export["name"], "duel");
+    assert!(export["planets"][0].get("growth_rate").is_some());
+}
+
+#[test]
+fn convert_writes_other_formats() {
+    let save = fixture("save_v1.json");
+    let out = cli(&[
+        "convert",
+        save.to_str().unwrap(),
+        "--girth",
+        "20",
+        "--format",
+        "csv",
+    ]);
+    assert!(out.status.success());
+    let csv = String::from_utf8(out.stdout).unwrap();
+    assert!(csv.starts_with("name,x,y,owner,ship_count\n"), "{}", csv);
+    assert_eq!(csv.trim_end().lines().count(), 4);
+
+    let out = cli(&[
+        "convert",
+        save.to_str().unwrap(),
+        "--girth",
+        "20",
+        "--format",
+        "pdf",
+    ]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn maps_with_errors_fail() {
+    let dir = temp_dir("errors");
+    let broken = broken_save(&dir);
+
+    let out = cli(&["convert", broken.to_str().unwrap(), "--girth", "20"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+
+    let ok = fixture("save_v1.json");
+    let out = cli(&["validate", ok.to_str().unwrap(), broken.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.contains("save_v1.json: ok"), "{}", text);
+    assert!(text.contains("broken.json: error"), "{}", text);
+}
+
+#[test]
+fn batch_converts_every_save() {
+    let dir = temp_dir("batch");
+    for name in ["save_v0.json", "save_v1.json"] {
+        fs::copy(fixture(name), dir.join(name)).unwrap();
+    }
+    broken_save(&dir);
+
+    let out = cli(&["batch", dir.to_str().unwrap(), "--girth", "20"]);
+    // the broken save fails, the others are still converted
+    assert_eq!(out.status.code(), Some(1));
+    for name in ["save_v0", "save_v1"] {
+        let text = fs::read_to_string(dir.join("export").join(format!("{}.json", name))).unwrap();
+        let export: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(export["name"], name);
+    }
+    assert!(!dir.join("export/broken.json").exists());
+}
+
+#[test]
+fn usage_errors() {
+    let save = fixture("save_v1.json");
+    assert_eq!(cli(&[]).status.code(), Some(2));
+    assert_eq!(cli(&["explode"]).status.code(), Some(2));
+    assert_eq!(
+        cli(&["convert", save.to_str().unwrap()]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        cli(&["convert", save.to_str().unwrap(), "--girth", "-3"])
+            .status
+            .code(),
+        Some(2)
+    );
+    assert!(cli(&["stats", save.to_str().unwrap()]).status.success());
+}
@@ -0,0 +1,79 @@
+use mapbuilder::clipboard::{copy, unique_names, Paste};
+use mapbuilder::geometry::{symmetry::Symmetry, GridParams, Location, MapType};
+use mapbuilder::planet::{PlanetData, Player};
+use mapbuilder::scene::{Scene, SceneConfig};
+
+fn planet(name: &str) -> PlanetData {
+    PlanetData {
+        player: Player(1),
+        name: name.to_string(),
+        ship_count: 5,
+        ..Default::default()
+    }
+}
+
+fn config(ty: MapType) -> SceneConfig {
+    SceneConfig {
+        ty,
+        params: GridParams::default(),
+        symmetry: Symmetry::default(),
+    }
+}
+
+fn paste_of(ty: MapType, planets: &[(Location, PlanetData)]) -> Paste {
+    let text = copy(config(ty), planets.iter().map(|(l, d)| (*l, d)));
+    let scene: Scene = serde_json::from_str(&text).unwrap();
+    Paste::new(scene).unwrap()
+}
+
+#[test]
+fn middle_planet_lands_under_the_mouse() {
+    let planets = [
+        (Location::new(0, 0), planet("a")),
+        (Location::new(1, 0), planet("b")),
+        (Location::new(2, 0), planet("c")),
+    ];
+    let paste = paste_of(MapType::Squares, &planets);
+    let grid = MapType::Squares.grid();
+
+    let plan = paste.plan(grid.as_ref(), Location::new(10, 5), &[]);
+    assert!(!plan.blocked);
+    assert_eq!(
+        plan.targets,
+        [
+            Location::new(9, 5),
+            Location::new(10, 5),
+            Location::new(11, 5)
+        ]
+    );
+
+    let plan = paste.plan(grid.as_ref(), Location::new(10, 5), &[Location::new(11, 5)]);
+    assert!(plan.blocked);
+}
+
+#[test]
+fn pastes_onto_another_grid() {
+    let planets = [
+        (Location::new(0, 0), planet("a")),
+        (Location::new(3, 0), planet("b")),
+    ];
+    let paste = paste_of(MapType::Hexagons, &planets);
+    let grid = MapType::Squares.grid();
+
+    let plan = paste.plan(grid.as_ref(), Location::new(0, 0), &[]);
+    assert!(!plan.blocked);
+    assert_eq!(plan.targets.len(), 2);
+    assert_ne!(plan.targets[0], plan.targets[1]);
+}
+
+#[test]
+fn nothing_to_paste() {
+    let scene: Scene = serde_json::from_str(&copy(config(MapType::Squares), [])).unwrap();
+    assert!(Paste::new(scene).is_none());
+}
+
+#[test]
+fn colliding_names_are_numbered() {
+    let names = unique_names(["vega", "sol", "vega", "new"], ["vega", "vega 2", "sol"]);
+    assert_eq!(names, ["vega 3", "sol 2", "vega 4", "new"]);
+}
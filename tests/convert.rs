@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use mapbuilder::geometry::{convert::relocate, FreeGrid, Location, MapType};
+
+fn block(w: i32, h: i32) -> Vec<Location> {
+    (0..w)
+        .flat_map(|x| (0..h).map(move |y| Location::new(x, y)))
+        .collect()
+}
+
+#[test]
+fn same_grid_keeps_every_location() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let locations: Vec<_> = block(6, 6)
+            .into_iter()
+            .filter(|l| grid.is_cell(*l))
+            .collect();
+        for (loc, placement) in
+            locations
+                .iter()
+                .zip(relocate(grid.as_ref(), grid.as_ref(), &locations))
+        {
+            assert_eq!(placement.to, *loc, "{:?}", ty);
+            assert_eq!(placement.taken, None, "{:?}", ty);
+        }
+    }
+}
+
+#[test]
+fn planets_stay_close_and_never_share_a_cell() {
+    // every other square, about as dense as the coarsest grid
+    let locations: Vec<_> = block(8, 8)
+        .into_iter()
+        .map(|l| Location::new(2 * l.x, 2 * l.y))
+        .collect();
+    let from = MapType::Squares.grid();
+    for ty in MapType::ALL {
+        let to = ty.grid();
+        let placements = relocate(from.as_ref(), to.as_ref(), &locations);
+        assert_eq!(placements.len(), locations.len());
+
+        let cells: HashSet<_> = placements.iter().map(|p| p.to).collect();
+        assert_eq!(cells.len(), placements.len(), "{:?}", ty);
+
+        for (loc, placement) in locations.iter().zip(&placements) {
+            let d = from.center(*loc).dist(to.center(placement.to));
+            assert!(d < 2.5, "{:?}: {:?} moved {}", ty, loc, d);
+            if placement.taken.is_none() {
+                assert_eq!(to.cell_at(from.center(*loc)), Some(placement.to));
+            }
+        }
+    }
+}
+
+#[test]
+fn collisions_are_reported() {
+    // a dense block of squares onto free positions snapped two units apart
+    let locations = block(4, 4);
+    let from = MapType::Squares.grid();
+    let to = FreeGrid { snap: Some(2.) };
+    let placements = relocate(from.as_ref(), &to, &locations);
+
+    let cells: HashSet<_> = placements.iter().map(|p| p.to).collect();
+    assert_eq!(cells.len(), locations.len());
+
+    // only one planet gets each snapped spot, the others say where they wanted to go
+    let moved: Vec<_> = placements.iter().filter(|p| p.taken.is_some()).collect();
+    assert_eq!(moved.len(), locations.len() - 9);
+    for p in moved {
+        assert_ne!(Some(p.to), p.taken);
+    }
+}
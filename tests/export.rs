@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+
+use mapbuilder::{
+    attributes::{attribute, AttributeValue},
+    export::{
+        format, svg::Svg, Csv, ExportFormat, ExportMap, ExportOptions, PlanetWars, PrettyJson,
+        FORMATS,
+    },
+    geometry::{symmetry::Symmetry, GridParams, MapType},
+    planet::{Location, PlanetData, Player, Players},
+    scene::SceneConfig,
+};
+use serde_json::Value;
+
+fn planets() -> Vec<(Location, PlanetData)> {
+    let mut home = PlanetData {
+        player: Player(1),
+        name: "home, sweet \"home\"".into(),
+        ship_count: 100,
+        ..Default::default()
+    };
+    home.attributes.set(
+        attribute("tags").unwrap(),
+        AttributeValue::Text("a, b".into()),
+    );
+    vec![
+        (Location::new(0, 0), home),
+        (
+            Location::new(4, 0),
+            PlanetData {
+                player: Player(2),
+                name: "away".into(),
+                ship_count: 100,
+                ..Default::default()
+            },
+        ),
+        (
+            Location::new(2, 0),
+            PlanetData {
+                name: "middle".into(),
+                ship_count: 7,
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+fn options() -> ExportOptions {
+    ExportOptions {
+        girth: 20.,
+        name: "line".into(),
+        attributes: false,
+        labels: false,
+        distances: false,
+    }
+}
+
+fn export_on(ty: MapType, format: &dyn ExportFormat, options: ExportOptions) -> String {
+    let planets = planets();
+    let planets: Vec<_> = planets.iter().map(|(l, d)| (*l, d)).collect();
+    let config = SceneConfig {
+        ty,
+        params: GridParams::default(),
+        symmetry: Symmetry::default(),
+    };
+    let content = format.export(&ExportMap {
+        planets: &planets,
+        config: &config,
+        players: &Players::default(),
+        options: &options,
+    });
+    String::from_utf8(content).unwrap()
+}
+
+fn export(format: &dyn ExportFormat, attributes: bool) -> String {
+    let options = ExportOptions {
+        attributes,
+        ..options()
+    };
+    export_on(MapType::Squares, format, options)
+}
+
+#[test]
+fn formats_are_found_by_id() {
+    let ids: HashSet<_> = FORMATS.iter().map(|f| f.id()).collect();
+    assert_eq!(ids.len(), FORMATS.len());
+    assert_eq!(FORMATS[0].id(), PlanetWars.id());
+    for f in FORMATS {
+        assert_eq!(format(f.id()).unwrap().label(), f.label());
+    }
+    assert!(format("docx").is_none());
+}
+
+#[test]
+fn planetwars_json_spans_the_longest_expedition() {
+    let json: Value = serde_json::from_str(&export(&PlanetWars, false)).unwrap();
+    assert_eq!(json["name"], "line");
+    let planets = json["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), 3);
+    assert_eq!(planets[0]["owner"], 1);
+    assert_eq!(planets[2]["owner"], Value::Null);
+    let span = planets[1]["x"].as_f64().unwrap() - planets[0]["x"].as_f64().unwrap();
+    assert!((span - 20.).abs() < 1e-3, "{}", span);
+    assert!(planets[0].get("tags").is_none());
+    // planetwars reads nothing but the planets and the name
+    assert_eq!(json.as_object().unwrap().len(), 2);
+
+    let json: Value = serde_json::from_str(&export(&PlanetWars, true)).unwrap();
+    assert_eq!(json["planets"][0]["tags"], serde_json::json!(["a", "b"]));
+}
+
+#[test]
+fn csv_has_a_row_per_planet() {
+    let csv = export(&Csv, false);
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines[0], "name,x,y,owner,ship_count");
+    assert_eq!(lines.len(), 4);
+    assert!(
+        lines[1].starts_with("\"home, sweet \"\"home\"\"\","),
+        "{}",
+        lines[1]
+    );
+    assert!(lines[1].ends_with(",1,100"), "{}", lines[1]);
+    assert!(lines[3].starts_with("middle,"), "{}", lines[3]);
+    assert!(lines[3].ends_with(",,7"), "{}", lines[3]);
+
+    let csv = export(&Csv, true);
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "name,x,y,owner,ship_count,growth_rate,size,production,tags"
+    );
+    assert!(lines[1].ends_with(",1,100,1,1.0,0,a;b"), "{}", lines[1]);
+}
+
+#[test]
+fn pretty_json_adds_metadata() {
+    let pretty = export(&PrettyJson, false);
+    assert!(pretty.lines().count() > 10);
+    let json: Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(json["metadata"]["map_type"], "Squares");
+    assert_eq!(json["metadata"]["longest_expedition"], 20.);
+    assert_eq!(json["metadata"]["planets"], 3);
+    assert_eq!(json["players"][1]["name"], "Player 2");
+    assert_eq!(json["players"][1]["team"], 2);
+
+    // the rest is the planetwars export
+    let mut plain: Value = serde_json::from_str(&export(&PlanetWars, false)).unwrap();
+    plain["metadata"] = json["metadata"].clone();
+    plain["players"] = json["players"].clone();
+    assert_eq!(json, plain);
+}
+
+#[test]
+fn svg_draws_the_grid_under_the_planets() {
+    let colors: Vec<_> = [Player(1), Player(2), Player(0)]
+        .iter()
+        .map(|p| {
+            let c = Players::default().color32(*p);
+            format!("fill=\"#{:02x}{:02x}{:02x}\"", c.r(), c.g(), c.b())
+        })
+        .collect();
+    for ty in MapType::ALL {
+        let svg = export_on(ty, &Svg, options());
+        assert!(svg.starts_with("<svg "), "{:?}", ty);
+        assert!(svg.trim_end().ends_with("</svg>"), "{:?}", ty);
+        for color in colors.iter() {
+            assert!(svg.contains(color.as_str()), "{:?} has no {}", ty, color);
+        }
+
+        let polygons = svg.matches("<polygon ").count();
+        if ty == MapType::Free {
+            assert_eq!(polygons, 3);
+        } else {
+            // the line of planets with a margin of cells around it
+            assert!(polygons > 3 + 10, "{:?}: {}", ty, polygons);
+        }
+        assert!(!svg.contains("<text"), "{:?}", ty);
+        assert!(!svg.contains("<line"), "{:?}", ty);
+    }
+}
+
+#[test]
+fn svg_labels_and_distances() {
+    let svg = export_on(
+        MapType::Squares,
+        &Svg,
+        ExportOptions {
+            labels: true,
+            distances: true,
+            ..options()
+        },
+    );
+    assert!(svg.contains(">home, sweet &quot;home&quot;</text>"));
+    assert!(svg.contains(">100</text>"));
+    assert!(svg.contains(">7</text>"));
+
+    // three planets on a line, every pair is close enough for a line
+    assert_eq!(svg.matches("<line ").count(), 3);
+    // the ends are the longest expedition apart, the middle is half way
+    assert!(svg.contains(">20</text>"));
+    assert_eq!(svg.matches(">10</text>").count(), 2);
+}
+
+#[test]
+fn svg_of_a_stray_planet_stays_small() {
+    let planets = planets();
+    let far = PlanetData {
+        name: "far".into(),
+        ..Default::default()
+    };
+    let mut planets: Vec<_> = planets.iter().map(|(l, d)| (*l, d)).collect();
+    planets.push((Location::new(100_000, 50_000), &far));
+    let config = SceneConfig {
+        ty: MapType::Hexagons,
+        params: GridParams::default(),
+        symmetry: Symmetry::default(),
+    };
+    let content = Svg.export(&ExportMap {
+        planets: &planets,
+        config: &config,
+        players: &Players::default(),
+        options: &options(),
+    });
+    let svg = String::from_utf8(content).unwrap();
+
+    // only the cells around the planets, not the whole box between them
+    let polygons = svg.matches("<polygon ").count();
+    assert!(polygons > 4 + 19, "{}", polygons);
+    assert!(polygons < 200, "{}", polygons);
+}
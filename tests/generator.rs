@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+
+use mapbuilder::generator::{generate, GenerateError, GeneratorSettings};
+use mapbuilder::geometry::{symmetry::SymmetryKind, Location, MapType};
+use mapbuilder::planet::{PlanetData, PlanetEvent};
+
+fn planets(settings: &GeneratorSettings, ty: MapType) -> Vec<(Location, PlanetData)> {
+    generate(settings, ty.grid().as_ref())
+        .unwrap()
+        .into_iter()
+        .map(|e| match e {
+            PlanetEvent::CreateNamed { loc, data } => (loc, data),
+            _ => panic!("only planets are created"),
+        })
+        .collect()
+}
+
+fn summary(planets: &[(Location, PlanetData)]) -> Vec<(Location, String, usize, usize)> {
+    planets
+        .iter()
+        .map(|(l, d)| (*l, d.name.clone(), d.player.0, d.ship_count))
+        .collect()
+}
+
+#[test]
+fn same_seed_same_map() {
+    let settings = GeneratorSettings {
+        seed: 42,
+        ..Default::default()
+    };
+    for ty in [MapType::Squares, MapType::Hexagons, MapType::Polar] {
+        let a = summary(&planets(&settings, ty));
+        assert_eq!(a, summary(&planets(&settings, ty)), "{:?}", ty);
+
+        let other = GeneratorSettings {
+            seed: 43,
+            ..settings.clone()
+        };
+        assert_ne!(a, summary(&planets(&other, ty)), "{:?}", ty);
+    }
+}
+
+#[test]
+fn maps_are_symmetric() {
+    for (symmetry, players) in [
+        (SymmetryKind::Horizontal, 2),
+        (SymmetryKind::Vertical, 2),
+        (SymmetryKind::Point, 2),
+        (SymmetryKind::Rotational(0), 4),
+    ] {
+        let settings = GeneratorSettings {
+            seed: 7,
+            players,
+            planets: 16,
+            symmetry,
+            ..Default::default()
+        };
+        let grid = MapType::Squares.grid();
+        let planets = planets(&settings, MapType::Squares);
+        assert_eq!(planets.len(), 16, "{:?}", symmetry);
+
+        let by_location: HashMap<_, _> = planets.iter().map(|(l, d)| (*l, d)).collect();
+        let sym = settings.symmetry();
+        for (loc, data) in planets.iter() {
+            for (other, k) in sym.counterparts(grid.as_ref(), *loc) {
+                let copy = by_location[&other];
+                assert_eq!(copy.ship_count, data.ship_count);
+                assert_eq!(copy.player.0, sym.permute(data.player.0, k));
+            }
+        }
+
+        let homes: Vec<_> = planets.iter().filter(|(_, d)| d.player.0 != 0).collect();
+        assert_eq!(homes.len(), players, "{:?}", symmetry);
+        let owners: HashSet<_> = homes.iter().map(|(_, d)| d.player.0).collect();
+        assert_eq!(owners, (1..=players).collect(), "{:?}", symmetry);
+    }
+}
+
+#[test]
+fn planets_keep_their_distance() {
+    let settings = GeneratorSettings {
+        seed: 3,
+        planets: 20,
+        min_spacing: 2.5,
+        ..Default::default()
+    };
+    let grid = MapType::Hexagons.grid();
+    let planets = planets(&settings, MapType::Hexagons);
+    let names: HashSet<_> = planets.iter().map(|(_, d)| &d.name).collect();
+    assert_eq!(names.len(), planets.len());
+
+    for (i, (a, _)) in planets.iter().enumerate() {
+        for (b, _) in &planets[i + 1..] {
+            assert!(grid.center(*a).dist(grid.center(*b)) >= 2.5);
+        }
+    }
+
+    for (_, d) in planets.iter() {
+        let ships = d.ship_count;
+        if d.player.0 == 0 {
+            assert!(settings.neutral_ships.contains(&ships));
+        } else {
+            assert!(settings.home_ships.contains(&ships));
+        }
+    }
+}
+
+#[test]
+fn bad_settings_are_refused() {
+    let grid = MapType::Squares.grid();
+    let mirror_three = GeneratorSettings {
+        players: 3,
+        symmetry: SymmetryKind::Horizontal,
+        ..Default::default()
+    };
+    assert_eq!(
+        generate(&mirror_three, grid.as_ref()).err(),
+        Some(GenerateError::Symmetry {
+            players: 3,
+            order: 2
+        })
+    );
+
+    let nobody = GeneratorSettings {
+        players: 0,
+        symmetry: SymmetryKind::None,
+        ..Default::default()
+    };
+    assert_eq!(
+        generate(&nobody, grid.as_ref()).err(),
+        Some(GenerateError::Players(0))
+    );
+
+    let spacing = GeneratorSettings {
+        min_spacing: 6.,
+        max_spacing: 3.,
+        ..Default::default()
+    };
+    assert_eq!(
+        generate(&spacing, grid.as_ref()).err(),
+        Some(GenerateError::Spacing)
+    );
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let ships = GeneratorSettings {
+        neutral_ships: 50..=5,
+        ..Default::default()
+    };
+    assert_eq!(
+        generate(&ships, grid.as_ref()).err(),
+        Some(GenerateError::ShipRange)
+    );
+}
@@ -0,0 +1,269 @@
+use mapbuilder::geometry::{
+    FreeGrid, Grid, GridParams, HexOrientation, Location, MapType, Point, TriangleOrientation,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const CASES: usize = 2000;
+
+/// Every map type where cells are areas, so all but [`MapType::Free`].
+fn with_area() -> impl Iterator<Item = MapType> {
+    MapType::ALL.into_iter().filter(|ty| *ty != MapType::Free)
+}
+
+/// Map types where neighboring cells meet along exactly one whole edge.
+fn edge_to_edge() -> impl Iterator<Item = MapType> {
+    with_area().filter(|ty| *ty != MapType::Polar)
+}
+
+fn random_location(grid: &dyn Grid, rng: &mut StdRng) -> Location {
+    loop {
+        let loc = Location::new(rng.gen_range(-40..40), rng.gen_range(-40..40));
+        if grid.is_cell(loc) {
+            return loc;
+        }
+    }
+}
+
+/// Uniform-ish point inside the (convex) polygon of `loc`, pulled slightly towards the center
+/// so that float noise on the shared edges does not decide the outcome.
+fn random_point_in(grid: &dyn Grid, loc: Location, rng: &mut StdRng) -> Point {
+    let center = grid.center(loc);
+    let polygon = grid.polygon(loc);
+    let i = rng.gen_range(0..polygon.len());
+    let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+
+    let (mut u, mut v): (f32, f32) = (rng.gen(), rng.gen());
+    if u + v > 1. {
+        u = 1. - u;
+        v = 1. - v;
+    }
+    let shrink = 0.995;
+    Point::new(
+        center.x + shrink * (u * (a.x - center.x) + v * (b.x - center.x)),
+        center.y + shrink * (u * (a.y - center.y) + v * (b.y - center.y)),
+    )
+}
+
+#[test]
+fn center_maps_back_to_cell() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..CASES {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            assert_eq!(grid.cell_at(grid.center(loc)), Some(loc), "{:?}", ty);
+        }
+    }
+}
+
+#[test]
+fn points_inside_polygon_map_back_to_cell() {
+    for ty in with_area() {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..CASES {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            for _ in 0..8 {
+                let p = random_point_in(grid.as_ref(), loc, &mut rng);
+                assert_eq!(grid.cell_at(p), Some(loc), "{:?} at {:?}", ty, p);
+            }
+        }
+    }
+}
+
+#[test]
+fn corners_are_close_to_the_cell() {
+    // picking just inside a corner is where the old hand tuned constants went wrong
+    for ty in with_area() {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..CASES / 4 {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            let center = grid.center(loc);
+            for corner in grid.polygon(loc) {
+                let p = Point::new(
+                    corner.x + (center.x - corner.x) * 0.02,
+                    corner.y + (center.y - corner.y) * 0.02,
+                );
+                assert_eq!(grid.cell_at(p), Some(loc), "{:?} near {:?}", ty, corner);
+            }
+        }
+    }
+}
+
+#[test]
+fn neighbors_are_symmetric() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..CASES / 4 {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            for n in grid.neighbors(loc) {
+                assert!(
+                    grid.neighbors(n).contains(&loc),
+                    "{:?}: {:?} {:?}",
+                    ty,
+                    loc,
+                    n
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn neighbors_share_an_edge() {
+    for ty in edge_to_edge() {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..CASES / 4 {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            let corners = grid.polygon(loc);
+            for n in grid.neighbors(loc) {
+                let shared = grid
+                    .polygon(n)
+                    .into_iter()
+                    .filter(|p| corners.iter().any(|c| c.dist(*p) < 1e-3))
+                    .count();
+                assert_eq!(shared, 2, "{:?}: {:?} {:?}", ty, loc, n);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_edge_has_a_neighbor() {
+    // all tilings are edge to edge, so there is exactly one neighbor across every edge
+    for ty in edge_to_edge() {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..CASES / 4 {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            assert_eq!(
+                grid.neighbors(loc).len(),
+                grid.polygon(loc).len(),
+                "{:?}: {:?}",
+                ty,
+                loc
+            );
+        }
+    }
+}
+
+/// Meshes are built once per variant and only moved and turned into place.
+fn assert_prototype_lines_up(grid: &dyn Grid, prototypes: &[Location], loc: Location, ty: MapType) {
+    let proto = prototypes[grid.variant(loc)];
+    let (from, to) = (grid.center(proto), grid.center(loc));
+    let (sin, cos) = (grid.rotation(loc) - grid.rotation(proto)).sin_cos();
+
+    let expected = grid.polygon(loc);
+    for p in grid.polygon(proto) {
+        let (x, y) = (p.x - from.x, p.y - from.y);
+        let p = Point::new(to.x + x * cos - y * sin, to.y + x * sin + y * cos);
+        assert!(
+            expected.iter().any(|e| e.dist(p) < 1e-3),
+            "{:?}: {:?} {:?}",
+            ty,
+            loc,
+            p
+        );
+    }
+}
+
+#[test]
+fn prototypes_line_up_with_cells() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let prototypes = grid.prototypes();
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..CASES / 4 {
+            let loc = random_location(grid.as_ref(), &mut rng);
+            assert_prototype_lines_up(grid.as_ref(), &prototypes, loc, ty);
+        }
+    }
+}
+
+#[test]
+fn free_positions_round_trip() {
+    let grid = FreeGrid::default();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..CASES {
+        let p = Point::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0));
+        let loc = grid.cell_at(p).unwrap();
+        assert!(
+            grid.center(loc).dist(p) <= 1. / FreeGrid::RESOLUTION,
+            "{:?}",
+            p
+        );
+        assert_eq!(grid.cell_at(grid.center(loc)), Some(loc));
+    }
+}
+
+#[test]
+fn free_positions_snap_to_spacing() {
+    let grid = FreeGrid { snap: Some(0.5) };
+    assert_eq!(
+        grid.cell_at(Point::new(0.2, 0.3)),
+        Some(Location::new(0, 500))
+    );
+    assert_eq!(
+        grid.cell_at(Point::new(-1.3, 2.76)),
+        Some(Location::new(-1500, 3000))
+    );
+
+    let mut rng = StdRng::seed_from_u64(8);
+    for _ in 0..CASES {
+        let p = Point::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0));
+        let c = grid.center(grid.cell_at(p).unwrap());
+        assert!((p.x - c.x).abs() <= 0.25 + 1e-3 && (p.y - c.y).abs() <= 0.25 + 1e-3);
+        assert!(((c.x * 2.).round() - c.x * 2.).abs() < 1e-3);
+        assert!(((c.y * 2.).round() - c.y * 2.).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn grid_params_keep_picking_exact() {
+    let params = [
+        GridParams {
+            size: 1.7,
+            hex_orientation: HexOrientation::Pointy,
+            triangle_orientation: TriangleOrientation::Columns,
+            square_aspect: 0.6,
+            octagon_cut: 0.15,
+            snap: Some(0.5),
+            sectors: 9,
+        },
+        GridParams {
+            size: 0.4,
+            square_aspect: 2.5,
+            octagon_cut: 0.4,
+            ..GridParams::default()
+        },
+    ];
+    for params in params {
+        for ty in with_area() {
+            let grid = ty.grid_with(&params);
+            let prototypes = grid.prototypes();
+            let mut rng = StdRng::seed_from_u64(9);
+            for _ in 0..CASES / 4 {
+                let loc = random_location(grid.as_ref(), &mut rng);
+                assert_eq!(grid.cell_at(grid.center(loc)), Some(loc), "{:?}", ty);
+                for _ in 0..4 {
+                    let p = random_point_in(grid.as_ref(), loc, &mut rng);
+                    assert_eq!(grid.cell_at(p), Some(loc), "{:?} at {:?}", ty, p);
+                }
+
+                assert_prototype_lines_up(grid.as_ref(), &prototypes, loc, ty);
+            }
+        }
+    }
+}
+
+#[test]
+fn grid_params_fill_in_defaults() {
+    let params: GridParams = serde_json::from_str(r#"{ "snap": 0.5, "sectors": 8 }"#).unwrap();
+    assert_eq!(params.snap, Some(0.5));
+    assert_eq!(params.sectors, 8);
+    assert_eq!(params.size, GridParams::default().size);
+    assert_eq!(params.hex_orientation, HexOrientation::Flat);
+}
@@ -0,0 +1,211 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use mapbuilder::geometry::{polar::PolarGrid, Grid, Location, MapType, Point};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Plain breadth first search over `neighbors`, the reference for the closed forms.
+fn bfs(grid: &dyn Grid, from: Location, max: u32) -> HashMap<Location, u32> {
+    let mut dist = HashMap::from([(from, 0)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(loc) = queue.pop_front() {
+        let d = dist[&loc];
+        if d == max {
+            continue;
+        }
+        for n in grid.neighbors(loc) {
+            if let Entry::Vacant(e) = dist.entry(n) {
+                e.insert(d + 1);
+                queue.push_back(n);
+            }
+        }
+    }
+    dist
+}
+
+#[test]
+fn distance_matches_breadth_first_search() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            let from = Location::new(rng.gen_range(-9..9), rng.gen_range(-9..9));
+            if !grid.is_cell(from) {
+                continue;
+            }
+            for (to, d) in bfs(grid.as_ref(), from, 7) {
+                assert_eq!(grid.distance(from, to), d, "{:?} {:?} {:?}", ty, from, to);
+                assert_eq!(grid.distance(to, from), d, "{:?} {:?} {:?}", ty, to, from);
+            }
+        }
+    }
+}
+
+#[test]
+fn rings_are_breadth_first_layers() {
+    for ty in MapType::ALL {
+        let grid = ty.grid();
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let center = Location::new(rng.gen_range(-9..9), rng.gen_range(-9..9));
+            if !grid.is_cell(center) {
+                continue;
+            }
+            let dist = bfs(grid.as_ref(), center, 5);
+            for radius in 0..=5 {
+                let ring = grid.ring(center, radius);
+                let expected: HashSet<_> = dist
+                    .iter()
+                    .filter(|(_, d)| **d == radius)
+                    .map(|(l, _)| *l)
+                    .collect();
+
+                assert_eq!(ring.len(), expected.len(), "{:?} {:?}", ty, center);
+                assert_eq!(ring.into_iter().collect::<HashSet<_>>(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn square_rings() {
+    let grid = MapType::Squares.grid();
+    assert_eq!(
+        grid.ring(Location::new(3, -2), 0),
+        vec![Location::new(3, -2)]
+    );
+    for radius in 1..6 {
+        assert_eq!(
+            grid.ring(Location::new(3, -2), radius).len(),
+            4 * radius as usize
+        );
+    }
+}
+
+#[test]
+fn triangle_orientation() {
+    let grid = MapType::Triangles.grid();
+    let sorted = |loc| {
+        let mut ns = grid.neighbors(loc);
+        ns.sort();
+        ns
+    };
+
+    // pointing up: the third neighbor is below, the row below is shifted the other way
+    assert_eq!(
+        sorted(Location::new(0, 0)),
+        vec![
+            Location::new(-1, -1),
+            Location::new(-1, 0),
+            Location::new(1, 0)
+        ]
+    );
+    assert_eq!(
+        sorted(Location::new(0, 1)),
+        vec![
+            Location::new(-1, 1),
+            Location::new(1, 0),
+            Location::new(1, 1)
+        ]
+    );
+    // pointing down: the third neighbor is above
+    assert_eq!(
+        sorted(Location::new(1, 0)),
+        vec![
+            Location::new(0, 0),
+            Location::new(0, 1),
+            Location::new(2, 0)
+        ]
+    );
+    // negative rows and columns keep the same parity rules
+    assert_eq!(
+        sorted(Location::new(-1, -1)),
+        vec![
+            Location::new(-2, -1),
+            Location::new(0, -1),
+            Location::new(0, 0)
+        ]
+    );
+    assert_eq!(
+        sorted(Location::new(-2, -3)),
+        vec![
+            Location::new(-3, -3),
+            Location::new(-1, -4),
+            Location::new(-1, -3)
+        ]
+    );
+
+    // an up and a down triangle stacked on each other
+    assert_eq!(grid.distance(Location::new(0, 0), Location::new(-1, -1)), 1);
+    // the same column two rows up is a detour around the shared corner
+    assert_eq!(grid.distance(Location::new(0, 0), Location::new(0, 2)), 4);
+    assert_eq!(grid.ring(Location::new(0, 0), 1).len(), 3);
+    assert_eq!(grid.ring(Location::new(1, 0), 1).len(), 3);
+}
+
+#[test]
+fn octagon_orientation() {
+    let grid = MapType::Octagons.grid();
+
+    assert_eq!(grid.neighbors(Location::new(0, 0)).len(), 8);
+    assert_eq!(grid.neighbors(Location::new(0, 1)).len(), 4);
+    assert_eq!(grid.neighbors(Location::new(-3, -1)).len(), 4);
+
+    // small squares only touch octagons
+    for n in grid.neighbors(Location::new(-3, -1)) {
+        assert_eq!(n.y.rem_euclid(2), 0);
+    }
+
+    // two octagons diagonal from each other are two steps apart, over or around the square
+    assert_eq!(grid.distance(Location::new(0, 0), Location::new(1, 2)), 2);
+    assert_eq!(grid.distance(Location::new(0, 1), Location::new(0, -1)), 2);
+    assert_eq!(grid.distance(Location::new(0, 1), Location::new(2, 1)), 3);
+    assert_eq!(grid.distance(Location::new(-1, -1), Location::new(0, 0)), 1);
+    assert_eq!(grid.ring(Location::new(0, 1), 1).len(), 4);
+    assert_eq!(grid.ring(Location::new(0, 0), 1).len(), 8);
+}
+
+#[test]
+fn polar_rings() {
+    let grid = PolarGrid { sectors: 6 };
+
+    assert_eq!(grid.neighbors(Location::new(0, 0)).len(), 6);
+    for ring in 1..PolarGrid::RINGS {
+        assert_eq!(grid.sectors_in(ring), 6 * ring);
+    }
+
+    // the first ring touches the middle, two sectors of the second ring and its own two
+    let mut ns = grid.neighbors(Location::new(0, 1));
+    ns.sort();
+    assert_eq!(
+        ns,
+        vec![
+            Location::new(0, 0),
+            Location::new(0, 2),
+            Location::new(1, 1),
+            Location::new(1, 2),
+            Location::new(5, 1)
+        ]
+    );
+
+    // sectors wrap around at the positive x axis
+    assert!(grid
+        .neighbors(Location::new(11, 2))
+        .contains(&Location::new(0, 2)));
+    assert_eq!(grid.distance(Location::new(0, 3), Location::new(9, 3)), 6);
+    assert_eq!(grid.ring(Location::new(0, 0), 2).len(), 12);
+
+    // nothing is picked past the outermost ring
+    let edge = PolarGrid::RINGS as f32 + 0.5;
+    assert!(grid.cell_at(Point::new(edge - 0.01, 0.)).is_some());
+    assert!(grid.cell_at(Point::new(edge + 0.01, 0.)).is_none());
+
+    // locations left over from another map type still get a mesh
+    let variants = grid.prototypes().len();
+    for loc in [
+        Location::new(0, -3),
+        Location::new(2, PolarGrid::RINGS + 10),
+    ] {
+        assert!(!grid.is_cell(loc));
+        assert!(grid.variant(loc) < variants);
+    }
+}
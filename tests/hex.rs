@@ -0,0 +1,120 @@
+use std::collections::{HashSet, VecDeque};
+
+use mapbuilder::geometry::{
+    hex::{self, Axial},
+    Location, MapType,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_location(rng: &mut StdRng) -> Location {
+    Location::new(rng.gen_range(-30..30), rng.gen_range(-30..30))
+}
+
+fn bfs_distance(a: Location, b: Location) -> u32 {
+    let mut seen = HashSet::from([a]);
+    let mut queue = VecDeque::from([(a, 0)]);
+    while let Some((loc, d)) = queue.pop_front() {
+        if loc == b {
+            return d;
+        }
+        for n in hex::neighbors(loc) {
+            if seen.insert(n) {
+                queue.push_back((n, d + 1));
+            }
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn axial_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..1000 {
+        let loc = random_location(&mut rng);
+        assert_eq!(Location::from_axial(loc.to_axial()), loc);
+        assert_eq!(Location::from_cube(loc.to_cube()), loc);
+
+        let cube = loc.to_cube();
+        assert_eq!(cube.q + cube.r + cube.s, 0);
+    }
+}
+
+#[test]
+fn axial_matches_world_position() {
+    let grid = MapType::Hexagons.grid();
+    let origin = grid.center(Location::from_axial(Axial::new(0, 0)));
+    let q = grid.center(Location::from_axial(Axial::new(1, 0)));
+    let r = grid.center(Location::from_axial(Axial::new(0, 1)));
+
+    assert!((q.x - origin.x - 1.5).abs() < 1e-5);
+    assert!((r.x - origin.x).abs() < 1e-5);
+    assert!((r.y - origin.y - 3f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn neighbors_are_at_distance_one() {
+    let grid = MapType::Hexagons.grid();
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..500 {
+        let loc = random_location(&mut rng);
+        let ns = hex::neighbors(loc);
+        assert_eq!(ns.len(), 6);
+        for n in &ns {
+            assert_eq!(hex::hex_distance(loc, *n), 1);
+            assert!(grid.center(loc).dist(grid.center(*n)) < 1.8);
+        }
+        assert_eq!(ns.iter().collect::<HashSet<_>>().len(), 6);
+    }
+}
+
+#[test]
+fn distance_matches_breadth_first_search() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..100 {
+        let a = random_location(&mut rng);
+        let b = Location::new(a.x + rng.gen_range(-4..4), a.y + rng.gen_range(-8..8));
+        assert_eq!(
+            hex::hex_distance(a, b),
+            bfs_distance(a, b),
+            "{:?} {:?}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn rings_have_the_right_cells() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..50 {
+        let center = random_location(&mut rng);
+        assert_eq!(hex::ring(center, 0), vec![center]);
+        for radius in 1..6 {
+            let ring = hex::ring(center, radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert_eq!(ring.iter().collect::<HashSet<_>>().len(), ring.len());
+            for (i, loc) in ring.iter().enumerate() {
+                assert_eq!(hex::hex_distance(center, *loc), radius);
+                let next = ring[(i + 1) % ring.len()];
+                assert_eq!(hex::hex_distance(*loc, next), 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn lines_are_connected() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..200 {
+        let a = random_location(&mut rng);
+        let b = random_location(&mut rng);
+        let line = hex::line(a, b);
+
+        assert_eq!(line.len() as u32, hex::hex_distance(a, b) + 1);
+        assert_eq!(line.first(), Some(&a));
+        assert_eq!(line.last(), Some(&b));
+        for w in line.windows(2) {
+            assert_eq!(hex::hex_distance(w[0], w[1]), 1);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Color, Entity};
+use mapbuilder::attributes::attribute;
+use mapbuilder::geometry::{symmetry::Symmetry, GridParams, Location, MapType};
+use mapbuilder::history::{History, MapState, Replay};
+use mapbuilder::map_config::MapEvent;
+use mapbuilder::planet::{PlanetData, PlanetEvent, Player};
+
+/// Applies events like the planet and map systems would, with fresh entities for new planets.
+struct Sim {
+    state: MapState,
+    history: History,
+    next: u32,
+    /// Seconds since the start.
+    time: f64,
+}
+
+impl Sim {
+    fn new() -> Self {
+        Self {
+            state: MapState {
+                planets: HashMap::new(),
+                ty: MapType::Squares,
+                params: GridParams::default(),
+                symmetry: Symmetry::default(),
+                bg_color: Color::GRAY,
+            },
+            history: History::default(),
+            next: 0,
+            time: 0.,
+        }
+    }
+
+    /// One frame of edits.
+    fn edit(&mut self, planet_events: Vec<PlanetEvent>, map_events: Vec<MapEvent>) {
+        self.time += 1. / 60.;
+        self.history
+            .record(&self.state, &planet_events, &map_events, self.time);
+        self.apply(
+            map_events
+                .into_iter()
+                .map(Replay::Map)
+                .chain(planet_events.into_iter().map(Replay::Planet))
+                .collect(),
+        );
+    }
+
+    /// Replaces the map like a load: the old planets are despawned without events and the
+    /// history is cleared instead of recording the new ones.
+    fn load(&mut self, ty: MapType, planets: Vec<(Location, PlanetData)>) {
+        self.history.clear();
+        self.state.planets.clear();
+        self.apply(
+            std::iter::once(Replay::Map(MapEvent::SetType(ty)))
+                .chain(
+                    planets
+                        .into_iter()
+                        .map(|(loc, data)| Replay::Planet(PlanetEvent::CreateNamed { loc, data })),
+                )
+                .collect(),
+        );
+    }
+
+    fn undo(&mut self) {
+        let replay = self.history.undo(&self.state);
+        self.apply(replay);
+    }
+
+    fn redo(&mut self) {
+        let replay = self.history.redo(&self.state);
+        self.apply(replay);
+    }
+
+    fn apply(&mut self, events: Vec<Replay>) {
+        for event in events {
+            match event {
+                Replay::Map(MapEvent::SetType(ty)) => self.state.ty = ty,
+                Replay::Map(MapEvent::SetParams(params)) => self.state.params = params,
+                Replay::Map(MapEvent::SetSymmetry(symmetry)) => self.state.symmetry = symmetry,
+                Replay::Map(_) => {}
+                Replay::Planet(event) => self.apply_planet(event),
+            }
+        }
+    }
+
+    fn apply_planet(&mut self, event: PlanetEvent) {
+        let planets = &mut self.state.planets;
+        match event {
+            PlanetEvent::Create { loc, player } => {
+                let data = PlanetData {
+                    player,
+                    name: format!("random {}", self.next),
+                    ship_count: 10,
+                    ..Default::default()
+                };
+                self.next += 1;
+                planets.insert(Entity::from_raw(self.next), (loc, data));
+            }
+            PlanetEvent::CreateNamed { loc, data } => {
+                self.next += 1;
+                planets.insert(Entity::from_raw(self.next), (loc, data));
+            }
+            PlanetEvent::Delete { id } => {
+                planets.remove(&id);
+            }
+            PlanetEvent::SetPlayer { id, player } => {
+                planets.get_mut(&id).unwrap().1.player = player
+            }
+            PlanetEvent::SetName { id, name } => planets.get_mut(&id).unwrap().1.name = name,
+            PlanetEvent::SetShipCount { id, amount } => {
+                planets.get_mut(&id).unwrap().1.ship_count = amount
+            }
+            PlanetEvent::SetSelected { .. } => {}
+            PlanetEvent::Move { id, to } => planets.get_mut(&id).unwrap().0 = to,
+            PlanetEvent::SetAttribute { id, key, value } => {
+                let attr = attribute(key).unwrap();
+                planets.get_mut(&id).unwrap().1.attributes.set(attr, value)
+            }
+        }
+    }
+
+    fn at(&self, loc: Location) -> Option<(Entity, PlanetData)> {
+        self.state
+            .planets
+            .iter()
+            .find(|(_, (l, _))| *l == loc)
+            .map(|(e, (_, d))| (*e, d.clone()))
+    }
+}
+
+#[test]
+fn deleted_planets_come_back_as_new_entities() {
+    let mut sim = Sim::new();
+    let loc = Location::new(2, 3);
+    sim.edit(
+        vec![PlanetEvent::Create {
+            loc,
+            player: Player(1),
+        }],
+        vec![],
+    );
+    let (id, data) = sim.at(loc).unwrap();
+    sim.edit(vec![PlanetEvent::SetShipCount { id, amount: 42 }], vec![]);
+    sim.edit(vec![PlanetEvent::Delete { id }], vec![]);
+    assert!(sim.at(loc).is_none());
+
+    sim.undo();
+    let (new_id, restored) = sim.at(loc).unwrap();
+    assert_ne!(new_id, id);
+    assert_eq!(restored.name, data.name);
+    assert_eq!(restored.ship_count, 42);
+
+    // older steps still find the planet under its new entity
+    sim.undo();
+    assert_eq!(sim.at(loc).unwrap().1.ship_count, 10);
+    sim.undo();
+    assert!(sim.at(loc).is_none());
+    assert!(!sim.history.can_undo());
+
+    // the name picked when it was first created comes back
+    sim.redo();
+    assert_eq!(sim.at(loc).unwrap().1.name, data.name);
+    sim.redo();
+    sim.redo();
+    assert!(sim.at(loc).is_none());
+    assert!(!sim.history.can_redo());
+}
+
+#[test]
+fn typing_is_one_step() {
+    let mut sim = Sim::new();
+    let loc = Location::new(0, 0);
+    sim.edit(
+        vec![PlanetEvent::CreateNamed {
+            loc,
+            data: PlanetData {
+                player: Player(0),
+                name: "a".to_string(),
+                ship_count: 1,
+                ..Default::default()
+            },
+        }],
+        vec![],
+    );
+    for name in ["ab", "abc", "abcd"] {
+        let (id, _) = sim.at(loc).unwrap();
+        sim.edit(
+            vec![PlanetEvent::SetName {
+                id,
+                name: name.to_string(),
+            }],
+            vec![],
+        );
+    }
+    assert_eq!(sim.at(loc).unwrap().1.name, "abcd");
+
+    sim.undo();
+    assert_eq!(sim.at(loc).unwrap().1.name, "a");
+    sim.redo();
+    assert_eq!(sim.at(loc).unwrap().1.name, "abcd");
+}
+
+#[test]
+fn map_events_are_undone() {
+    let mut sim = Sim::new();
+    let params = GridParams {
+        size: 2.,
+        ..Default::default()
+    };
+    sim.edit(vec![], vec![MapEvent::SetType(MapType::Hexagons)]);
+    sim.edit(vec![], vec![MapEvent::SetParams(params)]);
+    // moving the view is not an edit
+    sim.edit(vec![], vec![MapEvent::Focus(Location::new(1, 1))]);
+
+    sim.undo();
+    assert_eq!(sim.state.params, GridParams::default());
+    sim.undo();
+    assert_eq!(sim.state.ty, MapType::Squares);
+    assert!(!sim.history.can_undo());
+
+    sim.redo();
+    sim.redo();
+    assert_eq!(sim.state.ty, MapType::Hexagons);
+    assert_eq!(sim.state.params, params);
+}
+
+#[test]
+fn new_edits_drop_the_redo_steps() {
+    let mut sim = Sim::new();
+    sim.edit(vec![], vec![MapEvent::SetType(MapType::Hexagons)]);
+    sim.undo();
+    assert!(sim.history.can_redo());
+
+    sim.edit(
+        vec![PlanetEvent::Create {
+            loc: Location::new(0, 0),
+            player: Player(2),
+        }],
+        vec![],
+    );
+    assert!(!sim.history.can_redo());
+}
+
+#[test]
+fn moving_a_group_is_one_step() {
+    let mut sim = Sim::new();
+    let (a, b) = (Location::new(0, 0), Location::new(1, 0));
+    sim.edit(
+        vec![
+            PlanetEvent::Create {
+                loc: a,
+                player: Player(1),
+            },
+            PlanetEvent::Create {
+                loc: b,
+                player: Player(2),
+            },
+        ],
+        vec![],
+    );
+    let (id_a, data_a) = sim.at(a).unwrap();
+    let (id_b, data_b) = sim.at(b).unwrap();
+
+    // the first planet moves onto the cell the second one leaves
+    sim.edit(
+        vec![
+            PlanetEvent::Move { id: id_a, to: b },
+            PlanetEvent::Move {
+                id: id_b,
+                to: Location::new(2, 0),
+            },
+        ],
+        vec![],
+    );
+    assert_eq!(sim.at(b).unwrap().1.name, data_a.name);
+
+    sim.undo();
+    assert_eq!(sim.at(a).unwrap().1.name, data_a.name);
+    assert_eq!(sim.at(b).unwrap().1.name, data_b.name);
+    assert!(sim.at(Location::new(2, 0)).is_none());
+
+    sim.redo();
+    assert_eq!(sim.at(b).unwrap().1.name, data_a.name);
+    assert_eq!(sim.at(Location::new(2, 0)).unwrap().1.name, data_b.name);
+}
+
+#[test]
+fn loading_forgets_the_history() {
+    let mut sim = Sim::new();
+    let old = Location::new(5, 5);
+    sim.edit(
+        vec![PlanetEvent::Create {
+            loc: old,
+            player: Player(1),
+        }],
+        vec![],
+    );
+    sim.undo();
+    sim.redo();
+    assert!(sim.history.can_undo());
+
+    let loaded = Location::new(1, 2);
+    sim.load(
+        MapType::Hexagons,
+        vec![(
+            loaded,
+            PlanetData {
+                name: "loaded".to_string(),
+                ..Default::default()
+            },
+        )],
+    );
+    assert!(!sim.history.can_undo());
+    assert!(!sim.history.can_redo());
+
+    // undoing right after the load keeps the loaded map
+    sim.undo();
+    assert_eq!(sim.state.ty, MapType::Hexagons);
+    assert_eq!(sim.at(loaded).unwrap().1.name, "loaded");
+    assert!(sim.at(old).is_none());
+
+    // edits after the load undo back to the loaded map
+    let (id, _) = sim.at(loaded).unwrap();
+    sim.edit(vec![PlanetEvent::Delete { id }], vec![]);
+    sim.undo();
+    assert_eq!(sim.at(loaded).unwrap().1.name, "loaded");
+    assert!(!sim.history.can_undo());
+}
+
+#[test]
+fn separate_edits_of_a_value_are_separate_steps() {
+    let mut sim = Sim::new();
+    let loc = Location::new(0, 0);
+    sim.edit(
+        vec![PlanetEvent::Create {
+            loc,
+            player: Player(1),
+        }],
+        vec![],
+    );
+    let (id, _) = sim.at(loc).unwrap();
+
+    // dragging the value, one event per frame
+    for amount in [11, 12, 13] {
+        sim.edit(vec![PlanetEvent::SetShipCount { id, amount }], vec![]);
+    }
+    // a while later the same planet is edited again
+    sim.time += 5.;
+    sim.edit(vec![PlanetEvent::SetShipCount { id, amount: 50 }], vec![]);
+
+    sim.undo();
+    assert_eq!(sim.at(loc).unwrap().1.ship_count, 13);
+    sim.undo();
+    assert_eq!(sim.at(loc).unwrap().1.ship_count, 10);
+    sim.redo();
+    sim.redo();
+    assert_eq!(sim.at(loc).unwrap().1.ship_count, 50);
+}
+
+#[test]
+fn dragging_grid_settings_is_one_step() {
+    let mut sim = Sim::new();
+    for size in [1.5, 2., 2.5] {
+        let params = GridParams {
+            size,
+            ..Default::default()
+        };
+        sim.edit(vec![], vec![MapEvent::SetParams(params)]);
+    }
+    for x in [1., 2.] {
+        let mut symmetry = Symmetry::default();
+        symmetry.center.x = x;
+        sim.edit(vec![], vec![MapEvent::SetSymmetry(symmetry)]);
+    }
+
+    // the symmetry drag, then the size drag
+    sim.undo();
+    assert_eq!(sim.state.symmetry, Symmetry::default());
+    sim.undo();
+    assert_eq!(sim.state.params, GridParams::default());
+    assert!(!sim.history.can_undo());
+    sim.redo();
+    assert_eq!(sim.state.params.size, 2.5);
+
+    // a later drag is a step of its own
+    sim.redo();
+    sim.time += 5.;
+    sim.edit(vec![], vec![MapEvent::SetParams(GridParams::default())]);
+    sim.undo();
+    assert_eq!(sim.state.params.size, 2.5);
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use mapbuilder::{
+    attributes::{attribute, AttributeValue},
+    geometry::{GridParams, MapType},
+    import::{import, ImportError},
+    planet::{Player, Players},
+};
+use serde_json::json;
+
+/// A map like the editor exports it, with a ring of planets around a center one.
+fn export() -> String {
+    let mut planets = vec![json!({
+        "name": "center", "x": 40.0, "y": -7.0, "owner": null, "ship_count": 5,
+        "growth_rate": 3, "size": 2.5, "production": 0, "tags": ["home", "rich"],
+    })];
+    for i in 0..6 {
+        let a = i as f32 * std::f32::consts::TAU / 6.;
+        planets.push(json!({
+            "name": format!("p{}", i),
+            "x": 12. * a.cos() + 40.,
+            "y": 12. * a.sin() - 7.,
+            "owner": (i % 2 == 0).then_some(1 + i / 2),
+            "ship_count": i,
+        }));
+    }
+    json!({
+        "planets": planets,
+        "name": "six",
+        "players": [{ "id": 1, "name": "red", "team": 1 }, { "id": 3, "name": "blue", "team": 2 }],
+    })
+    .to_string()
+}
+
+#[test]
+fn planets_come_back_on_their_own_cells() {
+    for ty in MapType::ALL {
+        let imported = import(&export(), ty, GridParams::default(), 2.).unwrap();
+        assert_eq!(imported.name, "six");
+        assert_eq!(imported.report.planets, 7);
+
+        let scene = &imported.scene;
+        assert_eq!(scene.config.ty, ty);
+        let cells: HashSet<_> = scene.planets.iter().map(|p| p.location).collect();
+        assert_eq!(cells.len(), 7, "{:?}", ty);
+
+        let center = &scene.planets[0].data;
+        assert_eq!(center.name, "center");
+        assert_eq!(center.player, Player(0));
+        assert_eq!(center.ship_count, 5);
+        let p4 = &scene.planets[5].data;
+        assert_eq!(
+            (p4.name.as_str(), p4.player, p4.ship_count),
+            ("p4", Player(3), 4)
+        );
+    }
+}
+
+#[test]
+fn closest_planets_are_spacing_apart() {
+    let imported = import(&export(), MapType::Free, GridParams::default(), 3.).unwrap();
+    let grid = imported.scene.config.grid();
+    let points: Vec<_> = imported
+        .scene
+        .planets
+        .iter()
+        .map(|p| grid.center(p.location))
+        .collect();
+    // the center planet lands on the origin, the ring around it
+    assert!(points[0].dist(Default::default()) < 1e-3);
+    for p in &points[1..] {
+        assert!((p.dist(points[0]) - 3.).abs() < 1e-2, "{:?}", p);
+    }
+    assert!(imported.report.moved.is_empty());
+}
+
+#[test]
+fn players_and_attributes_are_restored() {
+    let imported = import(&export(), MapType::Hexagons, GridParams::default(), 2.).unwrap();
+    let players = imported.scene.players.unwrap();
+    assert!(players.count() >= 3);
+    assert_eq!(players.name(Player(1)), "red");
+    assert_eq!(players.get(Player(3)).unwrap().team, 2);
+
+    let attributes = &imported.scene.planets[0].data.attributes;
+    let get = |key| attributes.get(attribute(key).unwrap());
+    assert_eq!(get("growth_rate"), AttributeValue::Int(3));
+    assert_eq!(get("size"), AttributeValue::Float(2.5));
+    assert_eq!(get("tags"), AttributeValue::Text("home, rich".into()));
+    assert!(imported.scene.planets[1].data.attributes.is_empty());
+}
+
+#[test]
+fn broken_exports_are_refused() {
+    let params = GridParams::default();
+    assert!(matches!(
+        import("{", MapType::Squares, params, 2.),
+        Err(ImportError::Json(_))
+    ));
+    assert!(matches!(
+        import(r#"{ "name": "x" }"#, MapType::Squares, params, 2.),
+        Err(ImportError::Json(_))
+    ));
+    assert_eq!(
+        import(
+            r#"{ "planets": [], "name": "x" }"#,
+            MapType::Squares,
+            params,
+            2.
+        )
+        .unwrap_err(),
+        ImportError::Empty
+    );
+
+    let huge = json!({
+        "planets": [{ "name": "a", "x": 0., "y": 0., "owner": 4_000_000_000u64, "ship_count": 1 }],
+    });
+    assert_eq!(
+        import(&huge.to_string(), MapType::Squares, params, 2.).unwrap_err(),
+        ImportError::TooManyPlayers(4_000_000_000)
+    );
+    let huge = json!({
+        "planets": [{ "name": "a", "x": 0., "y": 0., "owner": 1, "ship_count": 1 }],
+        "players": [{ "id": Players::MAX + 1, "name": "x", "team": 1 }],
+    });
+    assert_eq!(
+        import(&huge.to_string(), MapType::Squares, params, 2.).unwrap_err(),
+        ImportError::TooManyPlayers(Players::MAX + 1)
+    );
+}
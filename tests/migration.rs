@@ -0,0 +1,70 @@
+use mapbuilder::geometry::{Location, MapType};
+use mapbuilder::migration::{load_scene, migrate, LoadError, VERSION};
+use mapbuilder::planet::Player;
+use serde_json::{json, Value};
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
+    .unwrap()
+}
+
+#[test]
+fn current_saves_round_trip() {
+    let text = fixture("save_v1.json");
+    let (scene, from) = load_scene(&text).unwrap();
+    assert_eq!(from, VERSION);
+
+    // saving again writes exactly the fixture, so format changes do not go unnoticed
+    let saved: Value = serde_json::from_str(&serde_json::to_string(&scene).unwrap()).unwrap();
+    let expected: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(saved, expected);
+}
+
+#[test]
+fn first_saves_still_load() {
+    let (scene, from) = load_scene(&fixture("save_v0_baseline.json")).unwrap();
+    assert_eq!(from, 0);
+    assert_eq!(scene.version, VERSION);
+    assert_eq!(scene.config.ty, MapType::Squares);
+    assert!(scene.players.is_none());
+
+    let names: Vec<_> = scene.planets.iter().map(|p| p.data.name.as_str()).collect();
+    assert_eq!(names, ["red home", "blue home", "middle"]);
+    assert_eq!(scene.planets[1].location, Location::new(3, 0));
+    assert!(scene.planets.iter().all(|p| p.data.attributes.is_empty()));
+}
+
+#[test]
+fn unversioned_saves_upgrade_to_the_current_format() {
+    let (old, from) = load_scene(&fixture("save_v0.json")).unwrap();
+    let (new, _) = load_scene(&fixture("save_v1.json")).unwrap();
+    assert_eq!(from, 0);
+    assert_eq!(
+        serde_json::to_value(&old).unwrap(),
+        serde_json::to_value(&new).unwrap()
+    );
+    let players = old.players.unwrap();
+    assert_eq!(players.name(Player(2)), "Blue");
+}
+
+#[test]
+fn newer_and_unknown_versions_are_refused() {
+    let newer = json!({ "version": VERSION + 1, "config": {}, "planets": [] });
+    assert_eq!(migrate(newer), Err(LoadError::Newer(VERSION + 1)));
+
+    let text = r#"{ "version": "two", "config": { "ty": "Squares" }, "planets": [] }"#;
+    assert_eq!(
+        load_scene(text).err(),
+        Some(LoadError::Version(json!("two")))
+    );
+
+    assert!(matches!(load_scene("not json"), Err(LoadError::Json(_))));
+    assert!(matches!(
+        load_scene(r#"{ "config": { "ty": "Squares" } }"#),
+        Err(LoadError::Format { version: 0, .. })
+    ));
+}
@@ -0,0 +1,51 @@
+use mapbuilder::planet::{Player, Players, COLORS};
+use mapbuilder::scene::Scene;
+
+#[test]
+fn defaults_match_the_old_colors() {
+    let players = Players::default();
+    assert_eq!(players.count(), COLORS.len() - 1);
+    for (i, color) in COLORS.iter().enumerate() {
+        let c = players.color32(Player(i));
+        assert_eq!([c.r(), c.g(), c.b()], [color.r(), color.g(), color.b()]);
+    }
+    assert_eq!(players.name(Player(0)), "Neutral");
+}
+
+#[test]
+fn any_number_of_players() {
+    let mut players = Players::default();
+    for _ in 0..10 {
+        players.add();
+    }
+    assert_eq!(players.count(), 16);
+    let last = Player(16);
+    assert_eq!(players.name(last), "Player 16");
+    assert_eq!(players.get(last).unwrap().team, 16);
+    assert_ne!(players.color32(last), players.color32(Player(15)));
+
+    // unknown players get a color instead of a panic
+    players.color32(Player(40));
+}
+
+#[test]
+fn saves_without_players_still_load() {
+    let old = r#"{
+        "config": { "ty": "Squares" },
+        "planets": []
+    }"#;
+    let scene: Scene = serde_json::from_str(old).unwrap();
+    assert!(scene.players.is_none());
+
+    let mut players = Players::default();
+    players.add();
+    players.players[2].name = "blue team".to_string();
+    players.players[2].team = 1;
+    let scene = Scene {
+        players: Some(players.clone()),
+        ..scene
+    };
+    let text = serde_json::to_string(&scene).unwrap();
+    let back: Scene = serde_json::from_str(&text).unwrap();
+    assert_eq!(back.players, Some(players));
+}
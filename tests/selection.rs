@@ -0,0 +1,84 @@
+use mapbuilder::geometry::{polar::PolarGrid, Location, MapType, Point};
+use mapbuilder::planet::{PlanetData, Player};
+use mapbuilder::selection::{plan_drop, plan_move, rename, within};
+
+#[test]
+fn box_corners_in_any_order() {
+    let (a, b) = (Point::new(-1., 2.), Point::new(3., -2.));
+    for p in [Point::new(0., 0.), Point::new(3., 2.), Point::new(-1., -2.)] {
+        assert!(within(a, b, p));
+        assert!(within(b, a, p));
+    }
+    assert!(!within(a, b, Point::new(3.5, 0.)));
+    assert!(!within(b, a, Point::new(0., -2.5)));
+}
+
+#[test]
+fn rename_fills_in_pattern() {
+    let data = PlanetData {
+        player: Player(2),
+        name: "vega".to_string(),
+        ship_count: 7,
+        ..Default::default()
+    };
+    assert_eq!(rename("{name}", 1, &data), "vega");
+    assert_eq!(rename("p{player}-{n}", 3, &data), "p2-3");
+    assert_eq!(rename("{name} {n}{n}", 4, &data), "vega 44");
+    assert_eq!(rename("plain", 9, &data), "plain");
+}
+
+#[test]
+fn moves_keep_their_shape() {
+    let grid = MapType::Squares.grid();
+    let moving = [Location::new(0, 0), Location::new(1, 2)];
+    let plan = plan_move(
+        grid.as_ref(),
+        Location::new(1, 2),
+        Location::new(4, 1),
+        &moving,
+        &[Location::new(5, 5)],
+    );
+    assert!(!plan.blocked);
+    assert_eq!(plan.targets, [Location::new(3, -1), Location::new(4, 1)]);
+}
+
+#[test]
+fn moves_onto_other_planets_are_blocked() {
+    let grid = MapType::Hexagons.grid();
+    let moving = [Location::new(0, 0), Location::new(1, 0)];
+    // shifting by one cell lands the first planet where the second one was, that is fine
+    let plan = plan_move(
+        grid.as_ref(),
+        Location::new(0, 0),
+        Location::new(1, 0),
+        &moving,
+        &[],
+    );
+    assert!(!plan.blocked);
+    assert_eq!(plan.targets[0], Location::new(1, 0));
+
+    let plan = plan_move(
+        grid.as_ref(),
+        Location::new(0, 0),
+        Location::new(1, 0),
+        &moving,
+        &[Location::new(2, 0)],
+    );
+    assert!(plan.blocked);
+}
+
+#[test]
+fn drops_off_the_grid_are_blocked() {
+    let grid = MapType::Polar.grid();
+    let edge = PolarGrid::RINGS as f32;
+    let plan = plan_drop(grid.as_ref(), &[Point::new(edge - 1., 0.)], &[]);
+    assert!(!plan.blocked);
+    assert!(grid.is_cell(plan.targets[0]));
+
+    let plan = plan_drop(
+        grid.as_ref(),
+        &[Point::new(0., 0.), Point::new(edge + 5., 0.)],
+        &[],
+    );
+    assert!(plan.blocked);
+}
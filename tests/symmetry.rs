@@ -0,0 +1,89 @@
+use mapbuilder::geometry::{
+    symmetry::{Symmetry, SymmetryKind},
+    Location, MapType, Point,
+};
+
+fn close(a: Point, b: Point) -> bool {
+    a.dist(b) < 1e-4
+}
+
+#[test]
+fn images_come_back_around() {
+    let center = Point::new(2., -1.);
+    let p = Point::new(5.5, 3.);
+    for kind in [
+        SymmetryKind::Horizontal,
+        SymmetryKind::Vertical,
+        SymmetryKind::Point,
+        SymmetryKind::Rotational(4),
+    ] {
+        let symmetry = Symmetry { kind, center };
+        let n = symmetry.order();
+        let once = symmetry.image(p, 1);
+        assert!(!close(once, p), "{:?}", kind);
+        assert!((once.dist(center) - p.dist(center)).abs() < 1e-4);
+
+        // doing the first step n times ends up where it started
+        let mut q = p;
+        for _ in 0..n {
+            q = symmetry.image(q, 1);
+        }
+        assert!(close(q, p), "{:?}", kind);
+    }
+}
+
+#[test]
+fn mirrors_swap_players() {
+    let symmetry = Symmetry {
+        kind: SymmetryKind::Horizontal,
+        center: Point::new(0., 0.),
+    };
+    assert_eq!(symmetry.permute(1, 1), 2);
+    assert_eq!(symmetry.permute(2, 1), 1);
+    // neutral and players past the symmetry keep their planets
+    assert_eq!(symmetry.permute(0, 1), 0);
+    assert_eq!(symmetry.permute(3, 1), 3);
+}
+
+#[test]
+fn rotations_cycle_players() {
+    let symmetry = Symmetry {
+        kind: SymmetryKind::Rotational(3),
+        center: Point::new(0., 0.),
+    };
+    let owners: Vec<_> = (0..3).map(|k| symmetry.permute(1, k)).collect();
+    assert_eq!(owners, vec![1, 2, 3]);
+    assert_eq!(symmetry.permute(3, 2), 2);
+}
+
+#[test]
+fn counterparts_skip_the_axis() {
+    let grid = MapType::Squares.grid();
+    // square centers sit on halves, so mirror over the middle of a column
+    let symmetry = Symmetry {
+        kind: SymmetryKind::Horizontal,
+        center: grid.center(Location::new(3, 0)),
+    };
+    assert!(symmetry
+        .counterparts(grid.as_ref(), Location::new(3, 5))
+        .is_empty());
+    assert_eq!(
+        symmetry.counterparts(grid.as_ref(), Location::new(1, 5)),
+        vec![(Location::new(5, 5), 1)]
+    );
+}
+
+#[test]
+fn rotational_counterparts_are_distinct() {
+    let grid = MapType::Hexagons.grid();
+    let symmetry = Symmetry {
+        kind: SymmetryKind::Rotational(6),
+        center: Point::new(0., 0.),
+    };
+    let loc = grid.cell_at(Point::new(4., 1.)).unwrap();
+    let others = symmetry.counterparts(grid.as_ref(), loc);
+    assert_eq!(others.len(), 5);
+    let ks: Vec<_> = others.iter().map(|(_, k)| *k).collect();
+    assert_eq!(ks, vec![1, 2, 3, 4, 5]);
+    assert!(others.iter().all(|(l, _)| *l != loc));
+}
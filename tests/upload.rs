@@ -0,0 +1,112 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+};
+
+use mapbuilder::upload::{post, UploadResult};
+
+/// A request as the mock server saw it.
+struct Request {
+    head: String,
+    body: String,
+}
+
+/// Answers one request with `status` and `body`, returns the url to post to and the request
+/// once it came in.
+fn mock_server(status: &'static str, body: &'static str) -> (String, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api/maps", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let length = head
+            .lines()
+            .find_map(|l| {
+                let (name, value) = l.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse().unwrap())
+            })
+            .unwrap_or(0);
+        let mut content = vec![0; length];
+        reader.read_exact(&mut content).unwrap();
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+        .unwrap();
+        tx.send(Request {
+            head,
+            body: String::from_utf8(content).unwrap(),
+        })
+        .unwrap();
+    });
+    (url, rx)
+}
+
+#[test]
+fn export_is_posted_as_json() {
+    let (url, requests) = mock_server("201 Created", "");
+    let content = r#"{"name":"duel","planets":[]}"#;
+    assert_eq!(post(&url, content), UploadResult::Done(201));
+
+    let request = requests.recv().unwrap();
+    assert!(
+        request.head.starts_with("POST /api/maps "),
+        "{}",
+        request.head
+    );
+    assert!(
+        request
+            .head
+            .to_ascii_lowercase()
+            .contains("content-type: application/json"),
+        "{}",
+        request.head
+    );
+    assert_eq!(request.body, content);
+}
+
+#[test]
+fn refusals_keep_the_status_and_answer() {
+    let (url, _requests) = mock_server("422 Unprocessable Entity", "name is taken");
+    let result = post(&url, "{}");
+    assert_eq!(
+        result,
+        UploadResult::Refused {
+            status: 422,
+            body: "name is taken".to_string()
+        }
+    );
+    assert_eq!(
+        result.to_string(),
+        "The server refused the map (422): name is taken"
+    );
+}
+
+#[test]
+fn unreachable_server_fails() {
+    // a port that was free a moment ago
+    let url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/api/maps", listener.local_addr().unwrap())
+    };
+    assert!(matches!(post(&url, "{}"), UploadResult::Failed(_)));
+    assert!(matches!(post("not a url", "{}"), UploadResult::Failed(_)));
+}
@@ -0,0 +1,134 @@
+use mapbuilder::geometry::{Location, MapType};
+use mapbuilder::planet::{PlanetData, Player, Players};
+use mapbuilder::validation::{validate, Issue, Rule, Severity};
+
+fn planet(name: &str, player: usize, ship_count: usize) -> PlanetData {
+    PlanetData {
+        player: Player(player),
+        name: name.to_string(),
+        ship_count,
+        ..Default::default()
+    }
+}
+
+fn check(planets: &[(Location, PlanetData)], girth: Option<f32>) -> Vec<Issue> {
+    let planets: Vec<_> = planets.iter().map(|(l, d)| (*l, d)).collect();
+    validate(
+        &planets,
+        MapType::Squares.grid().as_ref(),
+        &Players::default(),
+        girth,
+    )
+}
+
+fn rules(issues: &[Issue]) -> Vec<Rule> {
+    issues.iter().map(|i| i.rule).collect()
+}
+
+fn good_map() -> Vec<(Location, PlanetData)> {
+    vec![
+        (Location::new(0, 0), planet("a", 1, 100)),
+        (Location::new(5, 0), planet("b", 2, 100)),
+        (Location::new(2, 3), planet("c", 0, 10)),
+    ]
+}
+
+#[test]
+fn good_map_passes() {
+    assert_eq!(check(&good_map(), Some(20.)), vec![]);
+}
+
+#[test]
+fn empty_map_is_an_error() {
+    assert_eq!(rules(&check(&[], None)), vec![Rule::NoPlanets]);
+}
+
+#[test]
+fn single_planet_can_not_be_scaled() {
+    let issues = check(&[(Location::new(1, 1), planet("a", 1, 5))], Some(20.));
+    assert!(rules(&issues).contains(&Rule::NoSpread));
+    assert!(rules(&issues).contains(&Rule::SinglePlayer));
+    let spread = issues.iter().find(|i| i.rule == Rule::NoSpread).unwrap();
+    assert_eq!(spread.locations, vec![Location::new(1, 1)]);
+}
+
+#[test]
+fn names_have_to_be_unique() {
+    let mut map = good_map();
+    map[2].1.name = "a".to_string();
+    map.push((Location::new(7, 7), planet(" ", 0, 1)));
+    let issues = check(&map, None);
+    assert_eq!(rules(&issues), vec![Rule::EmptyName, Rule::DuplicateName]);
+    assert_eq!(
+        issues[1].locations,
+        vec![Location::new(0, 0), Location::new(2, 3)]
+    );
+}
+
+#[test]
+fn planets_can_not_share_a_cell() {
+    let mut map = good_map();
+    map.push((Location::new(5, 0), planet("d", 0, 1)));
+    let issues = check(&map, None);
+    assert_eq!(rules(&issues), vec![Rule::SharedCell]);
+    assert_eq!(issues[0].locations, vec![Location::new(5, 0)]);
+}
+
+#[test]
+fn players_are_checked() {
+    let mut map = good_map();
+    map[1].1.player = Player(1);
+    assert_eq!(rules(&check(&map, None)), vec![Rule::SinglePlayer]);
+
+    map[0].1.player = Player(0);
+    map[1].1.player = Player(0);
+    assert_eq!(rules(&check(&map, None)), vec![Rule::NoPlayers]);
+}
+
+#[test]
+fn warnings_come_after_errors() {
+    let mut map = good_map();
+    map[1].1.player = Player(3);
+    map[1].1.ship_count = 50;
+    map.push((Location::new(9, 9), planet("a", 0, 1)));
+    let issues = check(&map, Some(-1.));
+    assert_eq!(
+        rules(&issues),
+        vec![
+            Rule::Girth,
+            Rule::DuplicateName,
+            Rule::MissingPlayer,
+            Rule::UnevenStart
+        ]
+    );
+    let severities: Vec<_> = issues.iter().map(|i| i.severity()).collect();
+    assert_eq!(
+        severities,
+        vec![
+            Severity::Error,
+            Severity::Error,
+            Severity::Warning,
+            Severity::Warning
+        ]
+    );
+}
+
+#[test]
+fn players_are_called_by_name() {
+    let mut map = good_map();
+    map[1].1.player = Player(3);
+    map[1].1.ship_count = 50;
+    let mut players = Players::default();
+    players.players[1].name = "Red".to_string();
+
+    let planets: Vec<_> = map.iter().map(|(l, d)| (*l, d)).collect();
+    let issues = validate(&planets, MapType::Squares.grid().as_ref(), &players, None);
+    let messages: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Player 2 has no planets",
+            "Players start with different ships, Red: 100, Player 3: 50"
+        ]
+    );
+}
@@ -24,17 +24,31 @@ export async function load() {
     f.click();
 }
 
-export function exp(content) {
-    write(content, "export.json");
+export function exp(content, name) {
+    write(content, name);
 }
 
-export function upload(url, content) {
-    fetch(url, {
-        method: "POST",
-        body: content,
-        headers: {
-            'Content-Type': "application/json"
-        }
-    });
+export async function upload(url, content) {
+    try {
+        const res = await fetch(url, {
+            method: "POST",
+            body: content,
+            headers: {
+                'Content-Type': "application/json"
+            }
+        });
+        window.scene.finish_upload(res.status, await res.text());
+    } catch (e) {
+        window.scene.finish_upload(0, e.toString());
+    }
 }
 
+
+export function copy(content) {
+    navigator.clipboard.writeText(content);
+}
+
+export async function paste() {
+    const text = await navigator.clipboard.readText();
+    window.scene.finish_paste(text);
+}